@@ -0,0 +1,313 @@
+// The traffic between two endpoints at one level, both directions together,
+// e.g. everything between 10.0.0.1:51000 and 10.0.0.2:443 over TCP. Kept up to
+// date from the capture like `ArpTable`, only looking at frames it hasn't seen.
+
+use crate::packets::data_link::ethernet::EthernetFrame;
+use crate::packets::frame::Frame;
+use crate::packets::shared_objs::{DataLink, Network, Transport};
+use crate::packets::transport::tcp::TcpFlags;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Level {
+    #[default]
+    Ethernet,
+    Ipv4,
+    Ipv6,
+    Tcp,
+    Udp,
+}
+
+impl Level {
+    pub const ALL: [Level; 5] = [
+        Level::Ethernet,
+        Level::Ipv4,
+        Level::Ipv6,
+        Level::Tcp,
+        Level::Udp,
+    ];
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Ethernet => write!(f, "Ethernet"),
+            Level::Ipv4 => write!(f, "IPv4"),
+            Level::Ipv6 => write!(f, "IPv6"),
+            Level::Tcp => write!(f, "TCP"),
+            Level::Udp => write!(f, "UDP"),
+        }
+    }
+}
+
+// a MAC or IP address, with a port at the TCP and UDP levels
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Address {
+    pub address: Box<str>,
+    pub port: Option<u16>,
+}
+
+impl Address {
+    // a display filter for the packets to or from this address at `level`
+    pub fn filter(&self, level: Level) -> String {
+        let protocol = match level {
+            Level::Ethernet => "eth",
+            Level::Ipv4 => "ip",
+            Level::Ipv6 => "ipv6",
+            Level::Tcp | Level::Udp if self.address.contains(':') => "ipv6",
+            Level::Tcp | Level::Udp => "ip",
+        };
+        let filter = format!("{protocol}.addr == {}", self.address);
+        match (level, self.port) {
+            (Level::Tcp, Some(port)) => format!("{filter} && tcp.port == {port}"),
+            (Level::Udp, Some(port)) => format!("{filter} && udp.port == {port}"),
+            _ => filter,
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.port {
+            Some(port) if self.address.contains(':') => write!(f, "[{}]:{port}", self.address),
+            Some(port) => write!(f, "{}:{port}", self.address),
+            None => write!(f, "{}", self.address),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpState {
+    SynSent,
+    SynReceived,
+    Established,
+    // one side has sent a FIN
+    Closing,
+    // both have
+    Closed,
+    Reset,
+}
+
+impl fmt::Display for TcpState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TcpState::SynSent => write!(f, "SYN sent"),
+            TcpState::SynReceived => write!(f, "SYN received"),
+            TcpState::Established => write!(f, "established"),
+            TcpState::Closing => write!(f, "closing"),
+            TcpState::Closed => write!(f, "closed"),
+            TcpState::Reset => write!(f, "reset"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    pub level: Level,
+    // `a` sent the first frame seen
+    pub a: Address,
+    pub b: Address,
+    pub packets_a_to_b: u64,
+    pub bytes_a_to_b: u64,
+    pub packets_b_to_a: u64,
+    pub bytes_b_to_a: u64,
+    pub first_frame: i32,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    // only at the TCP level
+    pub tcp_state: Option<TcpState>,
+    // whether a and b have sent a FIN
+    fins: [bool; 2],
+}
+
+impl Conversation {
+    pub fn packets(&self) -> u64 {
+        self.packets_a_to_b + self.packets_b_to_a
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes_a_to_b + self.bytes_b_to_a
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    // a display filter for the packet table showing just this conversation
+    pub fn filter(&self) -> String {
+        format!(
+            "{} && {}",
+            self.a.filter(self.level),
+            self.b.filter(self.level)
+        )
+    }
+
+    fn new(level: Level, a: Address, b: Address, frame: &Frame) -> Conversation {
+        Conversation {
+            level,
+            a,
+            b,
+            packets_a_to_b: 0,
+            bytes_a_to_b: 0,
+            packets_b_to_a: 0,
+            bytes_b_to_a: 0,
+            first_frame: frame.id,
+            start: frame.timestamp,
+            end: frame.timestamp,
+            tcp_state: None,
+            fins: [false; 2],
+        }
+    }
+
+    // `from_a` is which side sent the segment
+    fn track_tcp(&mut self, flags: &TcpFlags, from_a: bool) {
+        let state = match self.tcp_state {
+            _ if flags.rst => TcpState::Reset,
+            // a new connection on the same ports starts over
+            _ if flags.syn && !flags.ack => {
+                self.fins = [false; 2];
+                TcpState::SynSent
+            }
+            _ if flags.syn => TcpState::SynReceived,
+            _ if flags.fin => {
+                self.fins[usize::from(!from_a)] = true;
+                match self.fins {
+                    [true, true] => TcpState::Closed,
+                    _ => TcpState::Closing,
+                }
+            }
+            Some(state @ (TcpState::Closing | TcpState::Closed | TcpState::Reset)) => state,
+            // the handshake finished, or the capture started mid connection
+            _ => TcpState::Established,
+        };
+        self.tcp_state = Some(state);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Conversations {
+    // in the order they were first seen
+    pub conversations: Vec<Conversation>,
+    // the level and both addresses, lower one first, to where it is in `conversations`
+    index: HashMap<(Level, Address, Address), usize>,
+    processed: usize,
+    // `Sniffer::generation` of the capture the conversations came from
+    generation: u32,
+}
+
+impl Conversations {
+    // only looks at frames it hasn't seen yet, starts over when `generation`
+    // says a new capture was started
+    pub fn update(&mut self, frames: &[Frame], generation: u32) {
+        if generation != self.generation {
+            *self = Conversations {
+                generation,
+                ..Default::default()
+            };
+        }
+
+        for frame in &frames[self.processed..] {
+            for (level, source, destination) in addresses(frame) {
+                self.add(frame, level, source, destination);
+            }
+        }
+        self.processed = frames.len();
+    }
+
+    pub fn at_level(&self, level: Level) -> impl Iterator<Item = &Conversation> {
+        { self.conversations.iter() }.filter(move |conversation| conversation.level == level)
+    }
+
+    fn add(&mut self, frame: &Frame, level: Level, source: Address, destination: Address) {
+        let key = match source <= destination {
+            true => (level, source.clone(), destination.clone()),
+            false => (level, destination.clone(), source.clone()),
+        };
+        let conversations = &mut self.conversations;
+        let i = *self.index.entry(key).or_insert_with(|| {
+            conversations.push(Conversation::new(level, source.clone(), destination, frame));
+            conversations.len() - 1
+        });
+
+        let conversation = &mut conversations[i];
+        let from_a = conversation.a == source;
+        let bytes = u64::from(frame.packet_header.len);
+        match from_a {
+            true => {
+                conversation.packets_a_to_b += 1;
+                conversation.bytes_a_to_b += bytes;
+            }
+            false => {
+                conversation.packets_b_to_a += 1;
+                conversation.bytes_b_to_a += bytes;
+            }
+        }
+        conversation.end = frame.timestamp;
+
+        if level == Level::Tcp {
+            if let Some(Transport::TCP(tcp)) = frame.payload.network().and_then(Network::transport)
+            {
+                conversation.track_tcp(&tcp.header.flags, from_a);
+            }
+        }
+    }
+}
+
+// the (level, source, destination) of every conversation `frame` is part of,
+// outermost level first
+pub fn addresses(frame: &Frame) -> Vec<(Level, Address, Address)> {
+    let address = |address: &str, port: Option<u16>| Address {
+        address: address.into(),
+        port,
+    };
+    let mut addresses = vec![];
+
+    if let DataLink::Ethernet(EthernetFrame { header, .. }) = &frame.payload {
+        addresses.push((
+            Level::Ethernet,
+            address(&header.source_mac, None),
+            address(&header.destination_mac, None),
+        ));
+    }
+
+    let (level, source, destination, transport) = match frame.payload.network() {
+        Some(Network::IPv4(ip)) => (
+            Level::Ipv4,
+            &ip.header.source_address,
+            &ip.header.destination_address,
+            &ip.payload,
+        ),
+        Some(Network::IPv6(ip)) => (
+            Level::Ipv6,
+            &ip.header.source,
+            &ip.header.destination,
+            &ip.payload,
+        ),
+        _ => return addresses,
+    };
+    addresses.push((level, address(source, None), address(destination, None)));
+
+    let ports = match transport {
+        Transport::TCP(tcp) => Some((
+            Level::Tcp,
+            tcp.header.source_port,
+            tcp.header.destination_port,
+        )),
+        Transport::UDP(udp) => Some((
+            Level::Udp,
+            udp.header.source_port,
+            udp.header.destination_port,
+        )),
+        _ => None,
+    };
+    if let Some((level, source_port, destination_port)) = ports {
+        addresses.push((
+            level,
+            address(source, Some(source_port)),
+            address(destination, Some(destination_port)),
+        ));
+    }
+    addresses
+}
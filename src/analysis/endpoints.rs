@@ -0,0 +1,112 @@
+// Traffic per endpoint, one side of the conversations in `conversations`: what
+// each MAC, IP address or address and port sent and received, and who to. Kept
+// up to date from the capture the same way, only looking at frames it hasn't seen.
+
+use crate::analysis::conversations::{addresses, Address, Level};
+use crate::packets::frame::Frame;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    pub level: Level,
+    pub address: Address,
+    pub packets_sent: u64,
+    pub bytes_sent: u64,
+    pub packets_received: u64,
+    pub bytes_received: u64,
+    pub first_frame: i32,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    // bytes sent to and received from each address talked to
+    pub peers: HashMap<Address, u64>,
+}
+
+impl Endpoint {
+    pub fn packets(&self) -> u64 {
+        self.packets_sent + self.packets_received
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes_sent + self.bytes_received
+    }
+
+    // the `count` peers the most bytes went to or came from, most first
+    pub fn top_peers(&self, count: usize) -> Vec<(&Address, u64)> {
+        let mut peers = { self.peers.iter() }
+            .map(|(peer, bytes)| (peer, *bytes))
+            .collect::<Vec<(&Address, u64)>>();
+        peers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        peers.truncate(count);
+        peers
+    }
+
+    fn new(level: Level, address: Address, frame: &Frame) -> Endpoint {
+        Endpoint {
+            level,
+            address,
+            packets_sent: 0,
+            bytes_sent: 0,
+            packets_received: 0,
+            bytes_received: 0,
+            first_frame: frame.id,
+            first_seen: frame.timestamp,
+            last_seen: frame.timestamp,
+            peers: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Endpoints {
+    // in the order they were first seen
+    pub endpoints: Vec<Endpoint>,
+    index: HashMap<(Level, Address), usize>,
+    processed: usize,
+    // `Sniffer::generation` of the capture the endpoints came from
+    generation: u32,
+}
+
+impl Endpoints {
+    // only looks at frames it hasn't seen yet, starts over when `generation`
+    // says a new capture was started
+    pub fn update(&mut self, frames: &[Frame], generation: u32) {
+        if generation != self.generation {
+            *self = Endpoints {
+                generation,
+                ..Default::default()
+            };
+        }
+
+        for frame in &frames[self.processed..] {
+            let bytes = u64::from(frame.packet_header.len);
+            for (level, source, destination) in addresses(frame) {
+                let sender = self.endpoint(frame, level, &source);
+                sender.packets_sent += 1;
+                sender.bytes_sent += bytes;
+                *sender.peers.entry(destination.clone()).or_default() += bytes;
+
+                let receiver = self.endpoint(frame, level, &destination);
+                receiver.packets_received += 1;
+                receiver.bytes_received += bytes;
+                *receiver.peers.entry(source).or_default() += bytes;
+            }
+        }
+        self.processed = frames.len();
+    }
+
+    pub fn at_level(&self, level: Level) -> impl Iterator<Item = &Endpoint> {
+        { self.endpoints.iter() }.filter(move |endpoint| endpoint.level == level)
+    }
+
+    fn endpoint(&mut self, frame: &Frame, level: Level, address: &Address) -> &mut Endpoint {
+        let endpoints = &mut self.endpoints;
+        let i = *{ self.index.entry((level, address.clone())) }.or_insert_with(|| {
+            endpoints.push(Endpoint::new(level, address.clone(), frame));
+            endpoints.len() - 1
+        });
+        let endpoint = &mut endpoints[i];
+        endpoint.last_seen = frame.timestamp;
+        endpoint
+    }
+}
@@ -0,0 +1,82 @@
+use super::{frame_mut, tcp_mut};
+use crate::packets::frame::Frame;
+use crate::packets::shared_objs::Application;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+
+// a request still waiting for its response
+struct Outstanding {
+    frame: i32,
+    // which of the frame's messages it is, pipelined requests can share a segment
+    message: usize,
+    uri: String,
+    timestamp: DateTime<Utc>,
+}
+
+// Pairs HTTP responses with the requests they answer. HTTP/1.x answers
+// requests in the order they were sent on a connection, so it's a queue per
+// TCP stream.
+#[derive(Default)]
+pub struct HttpTracker {
+    outstanding: HashMap<u32, VecDeque<Outstanding>>,
+}
+
+impl HttpTracker {
+    // `dissected` are the frames that were just given an application message,
+    // request frames given their response go in `changed`
+    pub fn process(&mut self, packets: &mut [Frame], dissected: &[i32], changed: &mut Vec<i32>) {
+        for &id in dissected {
+            let Some(frame) = frame_mut(packets, id) else {
+                continue;
+            };
+            let timestamp = frame.timestamp;
+            let Some(tcp) = tcp_mut(frame) else {
+                continue;
+            };
+            let Some(stream) = tcp.stream else {
+                continue;
+            };
+
+            // the requests answered by this frame, by frame and message
+            let mut answered = vec![];
+            for (message, application) in tcp.messages_mut().enumerate() {
+                match application {
+                    Application::HttpRequest(request) => {
+                        self.outstanding
+                            .entry(stream)
+                            .or_default()
+                            .push_back(Outstanding {
+                                frame: id,
+                                message,
+                                uri: request.uri.clone(),
+                                timestamp,
+                            });
+                    }
+                    // interim responses come before the real one for the same request
+                    Application::HttpResponse(response) if !response.is_informational() => {
+                        let Some(request) = { self.outstanding.get_mut(&stream) }
+                            .and_then(|requests| requests.pop_front())
+                        else {
+                            continue;
+                        };
+                        response.request_in = Some(request.frame);
+                        response.request_uri = Some(request.uri);
+                        response.time_since_request = Some(timestamp - request.timestamp);
+                        answered.push((request.frame, request.message));
+                    }
+                    _ => {}
+                }
+            }
+
+            for (request_frame, message) in answered {
+                let request = frame_mut(packets, request_frame)
+                    .and_then(tcp_mut)
+                    .and_then(|tcp| tcp.messages_mut().nth(message));
+                if let Some(Application::HttpRequest(request)) = request {
+                    request.response_in = Some(id);
+                    changed.push(request_frame);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use super::frame_mut;
+use crate::packets::frame::Frame;
+use crate::packets::shared_objs::{LayerData, Transport};
+use crate::packets::transport::icmp::IcmpEcho;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+// a reply later than this is taken as a reply to nothing, it keeps unanswered
+// requests (lost pings, sweeps) from piling up
+const ECHO_TIMEOUT_SECS: i64 = 30;
+
+// (requester, responder, identifier, sequence)
+type EchoKey = (Box<str>, Box<str>, u16, u16);
+
+// pairs ICMP echo requests with their replies and records the round trip time
+#[derive(Default)]
+pub struct EchoTracker {
+    outstanding: HashMap<EchoKey, (i32, DateTime<Utc>)>,
+}
+
+impl EchoTracker {
+    // request frames given their reply go in `changed`
+    pub fn process(&mut self, packets: &mut [Frame], changed: &mut Vec<i32>) {
+        let Some((frame, earlier)) = packets.split_last_mut() else {
+            return;
+        };
+        let (id, timestamp) = (frame.id, frame.timestamp);
+
+        let timeout = Duration::seconds(ECHO_TIMEOUT_SECS);
+        self.outstanding
+            .retain(|_, (_, request_time)| timestamp - *request_time < timeout);
+
+        let Some(network) = frame.payload.network_mut() else {
+            return;
+        };
+        let LayerData::Layer(ip) = network.get_layer_data() else {
+            return;
+        };
+        let source = Box::<str>::from(ip.source());
+        let destination = Box::<str>::from(ip.destination());
+
+        let Some(Transport::ICMP(icmp)) = network.transport_mut() else {
+            return;
+        };
+        let (is_request, is_reply) = (icmp.is_echo_request(), icmp.is_echo_reply());
+        let Some(echo) = icmp.echo.as_mut() else {
+            return;
+        };
+
+        if is_request {
+            let key = (source, destination, echo.identifier, echo.sequence);
+            self.outstanding.insert(key, (id, timestamp));
+        } else if is_reply {
+            let key = (destination, source, echo.identifier, echo.sequence);
+            let Some((request_id, request_time)) = self.outstanding.remove(&key) else {
+                return;
+            };
+            echo.request_in = Some(request_id);
+            echo.round_trip = Some(timestamp - request_time);
+
+            if let Some(request) = frame_mut(earlier, request_id).and_then(echo_mut) {
+                request.reply_in = Some(id);
+                changed.push(request_id);
+            }
+        }
+    }
+}
+
+fn echo_mut(frame: &mut Frame) -> Option<&mut IcmpEcho> {
+    match frame.payload.network_mut()?.transport_mut()? {
+        Transport::ICMP(icmp) => icmp.echo.as_mut(),
+        _ => None,
+    }
+}
@@ -0,0 +1,118 @@
+use super::frame_mut;
+use crate::packets::frame::Frame;
+use crate::packets::internet::ip::Ipv4Packet;
+use crate::packets::shared_objs::{Network, Transport};
+use crate::packets::transport::transport_parser::parse_transport_layer;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+// same as the linux default, ipfrag_time
+const REASSEMBLY_TIMEOUT_SECS: i64 = 30;
+const MAX_DATAGRAM: usize = 65_535;
+
+// (source, destination, identification, protocol)
+type FragmentKey = (Box<str>, Box<str>, u16, u8);
+
+struct PendingDatagram {
+    data: Vec<u8>,
+    // which bytes of `data` have been filled in so far
+    received: Vec<bool>,
+    // only known once the fragment without "more fragments" shows up
+    total_length: Option<usize>,
+    frames: Vec<i32>,
+    first_seen: DateTime<Utc>,
+}
+
+// Buffers IPv4 fragments until the whole datagram is there, then dissects it on
+// the frame that completed it and points every fragment at that frame.
+// Overlapping fragments keep the bytes that arrived first.
+#[derive(Default)]
+pub struct Ipv4Reassembler {
+    pending: HashMap<FragmentKey, PendingDatagram>,
+}
+
+impl Ipv4Reassembler {
+    // the earlier fragments of a datagram it put back together go in `changed`
+    pub fn process(&mut self, packets: &mut [Frame], changed: &mut Vec<i32>) {
+        let Some((frame, earlier)) = packets.split_last_mut() else {
+            return;
+        };
+        let (id, timestamp) = (frame.id, frame.timestamp);
+
+        let timeout = Duration::seconds(REASSEMBLY_TIMEOUT_SECS);
+        self.pending
+            .retain(|_, datagram| timestamp - datagram.first_seen < timeout);
+
+        let Some(Network::IPv4(ip)) = frame.payload.network_mut() else {
+            return;
+        };
+        if !ip.is_fragment() {
+            return;
+        }
+        let Transport::Other(fragment) = &ip.payload else {
+            return;
+        };
+
+        let header = &ip.header;
+        let key = (
+            header.source_address.clone(),
+            header.destination_address.clone(),
+            header.identification,
+            header.next_header.0,
+        );
+        let offset = header.flags_fragment_offset as usize * 8;
+        let end = offset + fragment.len();
+        if end > MAX_DATAGRAM {
+            return;
+        }
+
+        let datagram = self.pending.entry(key.clone()).or_insert_with(|| PendingDatagram {
+            data: Vec::new(),
+            received: Vec::new(),
+            total_length: None,
+            frames: Vec::new(),
+            first_seen: timestamp,
+        });
+        if datagram.data.len() < end {
+            datagram.data.resize(end, 0);
+            datagram.received.resize(end, false);
+        }
+        for (i, byte) in fragment.iter().enumerate() {
+            if !datagram.received[offset + i] {
+                datagram.data[offset + i] = *byte;
+                datagram.received[offset + i] = true;
+            }
+        }
+        if !header.flags.morefrag {
+            datagram.total_length = Some(end);
+        }
+        datagram.frames.push(id);
+
+        let Some(total_length) = datagram.total_length else {
+            return;
+        };
+        if !datagram.received[..total_length].iter().all(|&b| b) {
+            return;
+        }
+
+        let Some(datagram) = self.pending.remove(&key) else {
+            return;
+        };
+        complete(ip, id, &datagram.data[..total_length], &datagram.frames);
+
+        for fragment_id in &datagram.frames {
+            let earlier_ip =
+                frame_mut(earlier, *fragment_id).and_then(|frame| frame.payload.network_mut());
+            if let Some(Network::IPv4(earlier_ip)) = earlier_ip {
+                earlier_ip.reassembled_in = Some(id);
+                changed.push(*fragment_id);
+            }
+        }
+    }
+}
+
+fn complete(ip: &mut Ipv4Packet, id: i32, data: &[u8], frames: &[i32]) {
+    ip.payload = parse_transport_layer(ip.header.next_header, data);
+    ip.reassembled_in = Some(id);
+    ip.fragments = frames.to_vec().into_boxed_slice();
+}
@@ -0,0 +1,72 @@
+pub mod conversations;
+pub mod endpoints;
+pub mod http_pairing;
+pub mod icmp_echo;
+pub mod ip_reassembly;
+pub mod tcp_stream;
+
+use crate::packets::frame::Frame;
+use crate::packets::shared_objs::Transport;
+use crate::packets::transport::tcp::TcpPacket;
+use http_pairing::HttpTracker;
+use icmp_echo::EchoTracker;
+use ip_reassembly::Ipv4Reassembler;
+use tcp_stream::{TcpReassembler, TcpStream};
+
+// Dissection only ever sees one packet. Anything that needs to look across
+// packets (pairing requests with replies, reassembly, ...) runs here as frames
+// are received, with access to everything captured so far.
+#[derive(Default)]
+pub struct Analyzer {
+    ipv4_fragments: Ipv4Reassembler,
+    tcp: TcpReassembler,
+    http: HttpTracker,
+    echo: EchoTracker,
+}
+
+impl Analyzer {
+    // for when nothing will follow a tcp stream, `TcpStream::chunks` stays empty
+    pub fn without_stream_data() -> Analyzer {
+        Analyzer {
+            tcp: TcpReassembler::without_chunks(),
+            ..Default::default()
+        }
+    }
+
+    // `packets` is the whole capture with the newly received frame last. Returns
+    // the earlier frames that were changed, oldest first, anything that already
+    // looked at them (e.g. a display filter) has to look again.
+    pub fn process(&mut self, packets: &mut [Frame]) -> Vec<i32> {
+        let mut changed = Vec::new();
+        // reassembly first so everything after it sees the whole datagram
+        self.ipv4_fragments.process(packets, &mut changed);
+        let dissected = self.tcp.process(packets);
+        self.http.process(packets, &dissected, &mut changed);
+        self.echo.process(packets, &mut changed);
+
+        changed.extend(dissected);
+        let newest = packets.last().map(|frame| frame.id);
+        changed.retain(|&id| Some(id) != newest);
+        changed.sort_unstable();
+        changed.dedup();
+        changed
+    }
+
+    pub fn tcp_streams(&self) -> &[TcpStream] {
+        self.tcp.streams()
+    }
+}
+
+// the frame with this id, `packets` doesn't start at frame 0 once older frames
+// were let go (see `Sniffer::keep_last`), and those aren't found any more
+fn frame_mut(packets: &mut [Frame], id: i32) -> Option<&mut Frame> {
+    let first = packets.first()?.id;
+    packets.get_mut(usize::try_from(id.checked_sub(first)?).ok()?)
+}
+
+fn tcp_mut(frame: &mut Frame) -> Option<&mut TcpPacket> {
+    match frame.payload.network_mut()?.transport_mut()? {
+        Transport::TCP(tcp) => Some(tcp),
+        _ => None,
+    }
+}
@@ -0,0 +1,431 @@
+use super::{frame_mut, tcp_mut};
+use crate::packets::application::app_parser::{parse_app_stream, StreamContext, StreamParse};
+use crate::packets::application::http::HttpRequestType;
+use crate::packets::frame::Frame;
+use crate::packets::shared_objs::{Application, LayerData, Transport};
+use crate::packets::transport::tcp::{TcpAnalysis, TcpFlags, TcpPacket};
+use std::collections::{HashMap, VecDeque};
+
+// stop waiting for a hole to be filled once this much is buffered behind it
+const MAX_OUT_OF_ORDER: usize = 256 * 1024;
+// an application message still incomplete after this much data isn't one
+const MAX_PENDING: usize = 16 * 1024 * 1024;
+
+// (address, port)
+pub type Endpoint = (Box<str>, u16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    ClientToServer,
+    ServerToClient,
+}
+
+impl Direction {
+    fn reverse(self) -> Direction {
+        match self {
+            Direction::ClientToServer => Direction::ServerToClient,
+            Direction::ServerToClient => Direction::ClientToServer,
+        }
+    }
+}
+
+// one segment's worth of in-order data
+#[derive(Debug)]
+pub struct StreamChunk {
+    pub direction: Direction,
+    pub frame: i32,
+    pub data: Box<[u8]>,
+}
+
+#[derive(Default)]
+struct Flow {
+    // next sequence number expected, unknown until the first segment
+    next: Option<u32>,
+    // (sequence number, frame, data) of segments that can't be delivered yet
+    out_of_order: Vec<(u32, i32, Box<[u8]>)>,
+    // in-order bytes not yet used by an application message, along with the
+    // frame each run of them came from and where that run ends
+    pending: Vec<u8>,
+    pending_frames: Vec<(i32, usize)>,
+    undissectable: bool,
+    closed: bool,
+    // methods of the http requests sent this way still waiting for a response
+    http_requests: VecDeque<HttpRequestType>,
+}
+
+impl Flow {
+    fn buffered(&self) -> usize {
+        { self.out_of_order.iter() }
+            .map(|(_, _, data)| data.len())
+            .sum()
+    }
+
+    fn lowest_buffered(&self) -> Option<u32> {
+        { self.out_of_order.iter() }
+            .map(|(seq, _, _)| *seq)
+            .reduce(|a, b| if seq_lt(b, a) { b } else { a })
+    }
+
+    // a buffered segment that starts at or before `next`
+    fn take_deliverable(&mut self, next: u32) -> Option<(u32, i32, Box<[u8]>)> {
+        let i = { self.out_of_order.iter() }.position(|(seq, _, _)| seq_le(*seq, next))?;
+        Some(self.out_of_order.swap_remove(i))
+    }
+
+    fn highest_buffered_end(&self) -> Option<u32> {
+        { self.out_of_order.iter() }
+            .map(|(seq, _, data)| seq.wrapping_add(data.len() as u32))
+            .reduce(|a, b| if seq_lt(a, b) { b } else { a })
+    }
+}
+
+// an application message finished by `frame`, spanning `frames`
+struct Completed {
+    frame: i32,
+    application: Application,
+    frames: Box<[i32]>,
+}
+
+pub struct TcpStream {
+    pub index: u32,
+    pub client: Endpoint,
+    pub server: Endpoint,
+    // the payload of both directions in the order it was delivered, empty
+    // when the reassembler was told to skip it
+    pub chunks: Vec<StreamChunk>,
+    flows: [Flow; 2],
+    skip_chunks: bool,
+}
+
+impl TcpStream {
+    fn new(index: u32, client: Endpoint, server: Endpoint, skip_chunks: bool) -> TcpStream {
+        TcpStream {
+            index,
+            client,
+            server,
+            chunks: Vec::new(),
+            flows: Default::default(),
+            skip_chunks,
+        }
+    }
+
+    fn direction_of(&self, sender: &Endpoint) -> Direction {
+        if *sender == self.client {
+            Direction::ClientToServer
+        } else {
+            Direction::ServerToClient
+        }
+    }
+
+    fn ports(&self, direction: Direction) -> (u16, u16) {
+        match direction {
+            Direction::ClientToServer => (self.client.1, self.server.1),
+            Direction::ServerToClient => (self.server.1, self.client.1),
+        }
+    }
+
+    fn segment(
+        &mut self,
+        direction: Direction,
+        frame: i32,
+        tcp: &TcpPacket,
+        data: &[u8],
+        completed: &mut Vec<Completed>,
+    ) -> Option<TcpAnalysis> {
+        let flags = &tcp.header.flags;
+        let flow = &mut self.flows[direction as usize];
+        // the syn takes up a sequence number of its own
+        let seq = match flags.syn {
+            true => tcp.header.sequence_number.wrapping_add(1),
+            false => tcp.header.sequence_number,
+        };
+        if flags.syn {
+            flow.next = Some(seq);
+        }
+        let next = *flow.next.get_or_insert(seq);
+        let end = seq.wrapping_add(data.len() as u32);
+
+        let analysis = if data.is_empty() {
+            None
+        } else if seq_le(end, next) {
+            Some(TcpAnalysis::Retransmission)
+        } else if seq_lt(seq, next) {
+            Some(TcpAnalysis::Overlap)
+        } else if flow
+            .highest_buffered_end()
+            .is_some_and(|high| seq_lt(seq, high))
+        {
+            Some(TcpAnalysis::OutOfOrder)
+        } else if seq != next && flow.out_of_order.is_empty() {
+            Some(TcpAnalysis::PreviousSegmentNotCaptured)
+        } else {
+            None
+        };
+
+        // before delivering, a message running to the close may be finished by this
+        if flags.fin || flags.rst {
+            flow.closed = true;
+        }
+        if analysis != Some(TcpAnalysis::Retransmission) && !data.is_empty() {
+            flow.out_of_order.push((seq, frame, data.into()));
+            self.deliver(direction, completed);
+        } else if flags.fin || flags.rst {
+            self.dissect(direction, completed);
+        }
+
+        let flow = &mut self.flows[direction as usize];
+        // the fin takes up a sequence number too
+        if flags.fin && flow.next == Some(end) {
+            flow.next = Some(end.wrapping_add(1));
+        }
+        if flow.buffered() > MAX_OUT_OF_ORDER {
+            if let Some(lowest) = flow.lowest_buffered() {
+                self.skip_to(direction, lowest, completed);
+            }
+        }
+
+        analysis
+    }
+
+    // the other side acknowledged `ack`, anything before it we haven't seen
+    // was lost by the capture and is never coming
+    fn acknowledged(&mut self, direction: Direction, ack: u32, completed: &mut Vec<Completed>) {
+        let flow = &self.flows[direction as usize];
+        let Some(next) = flow.next else {
+            return;
+        };
+        if !seq_lt(next, ack) {
+            return;
+        }
+        let to = match flow.lowest_buffered() {
+            Some(lowest) if seq_lt(lowest, ack) => lowest,
+            _ => ack,
+        };
+        self.skip_to(direction, to, completed);
+    }
+
+    fn skip_to(&mut self, direction: Direction, to: u32, completed: &mut Vec<Completed>) {
+        let flow = &mut self.flows[direction as usize];
+        flow.next = Some(to);
+        // whatever message was in progress is missing its middle now
+        flow.pending.clear();
+        flow.pending_frames.clear();
+        self.deliver(direction, completed);
+    }
+
+    // moves every buffered segment that lines up with `next` into the stream
+    fn deliver(&mut self, direction: Direction, completed: &mut Vec<Completed>) {
+        let flow = &mut self.flows[direction as usize];
+        let Some(mut next) = flow.next else {
+            return;
+        };
+
+        while let Some((seq, frame, data)) = flow.take_deliverable(next) {
+            let already_have = next.wrapping_sub(seq) as usize;
+            let Some(data) = data.get(already_have..).filter(|data| !data.is_empty()) else {
+                continue;
+            };
+            next = next.wrapping_add(data.len() as u32);
+
+            if !flow.undissectable {
+                flow.pending.extend_from_slice(data);
+                flow.pending_frames.push((frame, flow.pending.len()));
+            }
+            if !self.skip_chunks {
+                self.chunks.push(StreamChunk {
+                    direction,
+                    frame,
+                    data: data.into(),
+                });
+            }
+        }
+        flow.next = Some(next);
+
+        self.dissect(direction, completed);
+    }
+
+    fn dissect(&mut self, direction: Direction, completed: &mut Vec<Completed>) {
+        let (source_port, destination_port) = self.ports(direction);
+        let [client, server] = &mut self.flows;
+        let (flow, other) = match direction {
+            Direction::ClientToServer => (client, server),
+            Direction::ServerToClient => (server, client),
+        };
+
+        while !flow.undissectable && !flow.pending.is_empty() {
+            let context = StreamContext {
+                closed: flow.closed && flow.out_of_order.is_empty(),
+                http_request: other.http_requests.front().cloned(),
+            };
+            match parse_app_stream(source_port, destination_port, &flow.pending, &context) {
+                // a dissector from another crate could get this wrong, taking nothing
+                // would never move on and taking more than there is can't be done
+                StreamParse::Message(_, used) if used == 0 || used > flow.pending.len() => {
+                    flow.undissectable = true
+                }
+                StreamParse::Message(application, used) => {
+                    match &application {
+                        Application::HttpRequest(request) => {
+                            flow.http_requests.push_back(request.method.clone());
+                        }
+                        Application::HttpResponse(response) if !response.is_informational() => {
+                            other.http_requests.pop_front();
+                        }
+                        _ => {}
+                    }
+                    let last = { flow.pending_frames.iter() }
+                        .position(|(_, end)| *end >= used)
+                        .unwrap_or(flow.pending_frames.len() - 1);
+                    let frames = { flow.pending_frames[..=last].iter() }
+                        .map(|(frame, _)| *frame)
+                        .collect::<Box<[i32]>>();
+                    completed.push(Completed {
+                        frame: frames[frames.len() - 1],
+                        application,
+                        frames,
+                    });
+
+                    flow.pending.drain(..used);
+                    flow.pending_frames.retain(|(_, end)| *end > used);
+                    for (_, end) in flow.pending_frames.iter_mut() {
+                        *end -= used;
+                    }
+                }
+                StreamParse::NeedMore => {
+                    flow.undissectable = flow.pending.len() > MAX_PENDING;
+                    break;
+                }
+                StreamParse::Unknown => flow.undissectable = true,
+            }
+        }
+
+        if flow.undissectable {
+            flow.pending.clear();
+            flow.pending_frames.clear();
+        }
+    }
+}
+
+// Follows every TCP connection in the capture, puts each direction's payload
+// back in sequence order and hands it to the application dissectors, so a
+// message split over several segments is decoded on the segment that finished
+// it. Segments are also marked as retransmissions, out of order and so on.
+#[derive(Default)]
+pub struct TcpReassembler {
+    // keyed on both endpoints, lower one first, so either direction finds it
+    connections: HashMap<(Endpoint, Endpoint), usize>,
+    streams: Vec<TcpStream>,
+    // don't keep a copy of every stream's payload, only following a stream needs it
+    skip_chunks: bool,
+}
+
+impl TcpReassembler {
+    pub fn without_chunks() -> TcpReassembler {
+        TcpReassembler {
+            skip_chunks: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn streams(&self) -> &[TcpStream] {
+        &self.streams
+    }
+
+    // returns the frames that were given an application message, in stream order
+    pub fn process(&mut self, packets: &mut [Frame]) -> Vec<i32> {
+        let Some(frame) = packets.last_mut() else {
+            return Vec::new();
+        };
+        let id = frame.id;
+        let Some(network) = frame.payload.network_mut() else {
+            return Vec::new();
+        };
+        let LayerData::Layer(ip) = network.get_layer_data() else {
+            return Vec::new();
+        };
+        let (source, destination) = (Box::from(ip.source()), Box::from(ip.destination()));
+        let Some(Transport::TCP(tcp)) = network.transport_mut() else {
+            return Vec::new();
+        };
+        let Application::Other(data) = &tcp.payload else {
+            return Vec::new();
+        };
+        let data = data.clone();
+
+        let sender = (source, tcp.header.source_port);
+        let receiver = (destination, tcp.header.destination_port);
+        let index = self.stream_for(sender.clone(), receiver, &tcp.header.flags);
+        let stream = &mut self.streams[index];
+        let direction = stream.direction_of(&sender);
+
+        let mut completed = Vec::new();
+        if tcp.header.flags.ack {
+            let ack = tcp.header.acknowledgment_number;
+            stream.acknowledged(direction.reverse(), ack, &mut completed);
+        }
+        tcp.analysis = stream.segment(direction, id, tcp, &data, &mut completed);
+        tcp.stream = Some(stream.index);
+
+        let mut dissected = Vec::new();
+        for Completed {
+            frame,
+            application,
+            frames,
+        } in completed
+        {
+            let Some(tcp) = frame_mut(packets, frame).and_then(tcp_mut) else {
+                continue;
+            };
+            // the first message a segment finished is its payload, the rest follow it
+            match tcp.payload {
+                Application::Other(_) => {
+                    tcp.payload = application;
+                    if frames.len() > 1 {
+                        tcp.pdu_frames = frames;
+                    }
+                }
+                _ => tcp.more_messages.push(application),
+            }
+            if !dissected.contains(&frame) {
+                dissected.push(frame);
+            }
+        }
+        dissected
+    }
+
+    fn stream_for(&mut self, sender: Endpoint, receiver: Endpoint, flags: &TcpFlags) -> usize {
+        let key = match sender <= receiver {
+            true => (sender.clone(), receiver.clone()),
+            false => (receiver.clone(), sender.clone()),
+        };
+        let opening = flags.syn && !flags.ack;
+
+        if let Some(&index) = self.connections.get(&key) {
+            // a fresh syn on a finished connection is a new connection on the same ports
+            let reused = opening && self.streams[index].flows.iter().any(|flow| flow.closed);
+            if !reused {
+                return index;
+            }
+        }
+
+        // whoever sent the first packet is the client, unless it's the syn-ack
+        let (client, server) = match flags.syn && flags.ack {
+            true => (receiver, sender),
+            false => (sender, receiver),
+        };
+        let index = self.streams.len();
+        let stream = TcpStream::new(index as u32, client, server, self.skip_chunks);
+        self.streams.push(stream);
+        self.connections.insert(key, index);
+        index
+    }
+}
+
+// sequence numbers wrap, so compare them the way tcp does
+fn seq_lt(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) < 0
+}
+
+fn seq_le(a: u32, b: u32) -> bool {
+    a == b || seq_lt(a, b)
+}
@@ -0,0 +1,263 @@
+// cnote without the gui, for servers and scripts. Prints one line per packet,
+// every layer's summary with -V, or JSON with -T.
+use cnote::export::json::JsonWriter;
+use cnote::filter::DisplayFilter;
+use cnote::packets::frame::Frame;
+use cnote::packets::packet_traits::Describable;
+use cnote::sniffer::Sniffer;
+use std::io::{self, StdoutLock, Write};
+use std::process::ExitCode;
+
+// how many of the latest packets a live capture holds on to, enough for the
+// analysis of anything still in flight
+const LIVE_PACKETS: usize = 50_000;
+
+const USAGE: &str = "usage: cnote-cli (-i <interface> | -r <file>) [options]
+
+  -i <interface>   capture live from an interface
+  -r <file>        read a pcap or pcapng file
+  -f <filter>      BPF capture filter, e.g. \"tcp port 443\"
+  -Y <filter>      display filter, e.g. \"ip.src == 10.0.0.1 && tcp\"
+  -c <count>       stop after this many packets are printed
+  -V               print every layer of each packet instead of one line
+  -T <format>      text (the default), json or ndjson
+  -D               list the interfaces and exit
+  -h               show this help
+
+A file is read to the end before anything is printed, so -Y sees fields that
+later packets fill in, e.g. http.response_in. A live capture prints each packet
+as it arrives and only keeps the latest ones, so those fields are never there.";
+
+#[derive(Default)]
+struct Options {
+    interface: Option<String>,
+    file: Option<String>,
+    capture_filter: Option<String>,
+    display_filter: Option<DisplayFilter>,
+    count: Option<usize>,
+    verbose: bool,
+    format: Format,
+    list_interfaces: bool,
+}
+
+#[derive(Default, PartialEq, Eq)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+    Ndjson,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("cnote-cli: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if options.list_interfaces {
+        return match pcap::Device::list() {
+            Ok(devices) => {
+                for (i, device) in devices.iter().enumerate() {
+                    match &device.desc {
+                        Some(desc) => println!("{}. {} ({desc})", i + 1, device.name),
+                        None => println!("{}. {}", i + 1, device.name),
+                    }
+                }
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("cnote-cli: couldn't list interfaces: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // nothing follows streams here, so there's no need to keep their payload
+    let mut sniffer = Sniffer {
+        skip_stream_data: true,
+        keep_last: options.interface.is_some().then_some(LIVE_PACKETS),
+        ..Default::default()
+    };
+    let capture_filter = options.capture_filter.as_deref();
+    let started = match (&options.interface, &options.file) {
+        (Some(interface), None) => sniffer.capture(interface, capture_filter),
+        (None, Some(file)) => sniffer.from_file(file.clone(), capture_filter),
+        _ => {
+            eprintln!("cnote-cli: give one of -i or -r\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    if let Err(e) = started {
+        eprintln!("cnote-cli: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut output = Output::new(&options);
+    let written = print_packets(&mut sniffer, &mut output, options.file.is_some());
+    match written.and_then(|_| output.finish()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => write_failed(e),
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+// None when only the help was asked for
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "-i" => options.interface = Some(value()?),
+            "-r" => options.file = Some(value()?),
+            "-f" => options.capture_filter = Some(value()?),
+            "-Y" => {
+                let filter = DisplayFilter::parse(&value()?)
+                    .map_err(|e| format!("bad display filter: {e}"))?;
+                options.display_filter = Some(filter);
+            }
+            "-c" => {
+                let count = value()?;
+                let count =
+                    { count.parse() }.map_err(|_| format!("-c takes a number, not {count}"))?;
+                options.count = Some(count);
+            }
+            "-V" => options.verbose = true,
+            "-T" => {
+                options.format = match value()?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    "ndjson" => Format::Ndjson,
+                    format => return Err(format!("unknown output format {format}")),
+                }
+            }
+            "-D" => options.list_interfaces = true,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+    Ok(Some(options))
+}
+
+// a file is read to the end first, so every packet has been analyzed before
+// the first is printed. A live capture is printed as it comes.
+fn print_packets(sniffer: &mut Sniffer, output: &mut Output, whole_file: bool) -> io::Result<()> {
+    match whole_file {
+        true => {
+            while sniffer.next_packet().is_some() {}
+            for frame in &sniffer.captured_packets {
+                if output.done() {
+                    break;
+                }
+                output.frame(frame)?;
+            }
+        }
+        false => {
+            while !output.done() {
+                let Some(frame) = sniffer.next_packet() else {
+                    break;
+                };
+                output.frame(frame)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// where the packets that pass -Y go, until -c of them have
+struct Output<'a> {
+    options: &'a Options,
+    json: Option<JsonWriter<StdoutLock<'static>>>,
+    text: StdoutLock<'static>,
+    printed: usize,
+}
+
+impl<'a> Output<'a> {
+    fn new(options: &'a Options) -> Output<'a> {
+        let json = match options.format {
+            Format::Text => None,
+            Format::Json => Some(JsonWriter::new(io::stdout().lock(), false)),
+            Format::Ndjson => Some(JsonWriter::new(io::stdout().lock(), true)),
+        };
+        Output {
+            options,
+            json,
+            text: io::stdout().lock(),
+            printed: 0,
+        }
+    }
+
+    fn done(&self) -> bool {
+        { self.options.count }.is_some_and(|count| self.printed >= count)
+    }
+
+    fn frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let shown =
+            { self.options.display_filter.as_ref() }.is_none_or(|filter| filter.matches(frame));
+        if !shown {
+            return Ok(());
+        }
+        match &mut self.json {
+            Some(json) => json.write(frame)?,
+            None if self.options.verbose => print_long(&mut self.text, frame)?,
+            None => print_line(&mut self.text, frame)?,
+        }
+        self.printed += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        match self.json.map(JsonWriter::finish) {
+            Some(finished) => finished.map(drop),
+            None => self.text.flush(),
+        }
+    }
+}
+
+// the other end of a pipe going away, e.g. `| head`, is just the end of the output
+fn write_failed(e: io::Error) -> ExitCode {
+    match e.kind() {
+        io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        _ => {
+            eprintln!("cnote-cli: couldn't write the output: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn print_line(out: &mut impl Write, frame: &Frame) -> io::Result<()> {
+    let [id, timestamp, source, destination, vlan, info] = frame.get_description().columns();
+    match vlan.is_empty() {
+        true => writeln!(
+            out,
+            "{id:>6}  {timestamp}  {source} -> {destination}  {info}"
+        ),
+        false => writeln!(
+            out,
+            "{id:>6}  {timestamp}  {source} -> {destination}  vlan {vlan}  {info}"
+        ),
+    }
+}
+
+fn print_long(out: &mut impl Write, frame: &Frame) -> io::Result<()> {
+    for (protocol, summary) in frame.get_long() {
+        writeln!(out, "{protocol}")?;
+        for line in summary.lines().filter(|line| !line.trim().is_empty()) {
+            writeln!(out, "    {line}")?;
+        }
+    }
+    writeln!(out)
+}
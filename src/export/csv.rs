@@ -0,0 +1,44 @@
+// Frames as the rows of the packet table, one line per frame under a header of
+// `Description::COLUMNS`. Fields are quoted the RFC 4180 way when they hold a
+// comma, a quote or a line break, so info strings open cleanly in a spreadsheet.
+
+use crate::packets::frame::Frame;
+use crate::packets::packet_traits::Describable;
+use crate::packets::shared_objs::Description;
+use std::io::{self, Write};
+
+// `frames` in the order they should appear, e.g. what the table is showing
+pub fn write_csv<'a>(
+    mut writer: impl Write,
+    frames: impl IntoIterator<Item = &'a Frame>,
+) -> io::Result<()> {
+    write_row(&mut writer, &Description::COLUMNS)?;
+    for frame in frames {
+        write_row(&mut writer, &frame.get_description().columns())?;
+    }
+    writer.flush()
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+fn write_row(writer: &mut impl Write, fields: &[impl AsRef<str>]) -> io::Result<()> {
+    let row = { fields.iter() }
+        .map(|field| quote(field.as_ref()))
+        .collect::<Vec<String>>()
+        .join(",");
+    // spreadsheets expect CRLF between records
+    write!(writer, "{row}\r\n")
+}
+
+fn quote(field: &str) -> String {
+    match field.contains([',', '"', '\r', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_owned(),
+    }
+}
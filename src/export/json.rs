@@ -0,0 +1,135 @@
+// Frames as JSON, one object per frame holding one object per layer:
+//
+//     {"id":0,"timestamp":"2024-05-01T10:00:00.000001Z","layers":[
+//       {"protocol":"frame","fields":{"frame.number":0,"frame.len":74,...}},
+//       {"protocol":"ip","fields":{"ip.src":"10.0.0.1","ip.addr":["10.0.0.1","10.0.0.2"],...}},
+//       ...]}
+//
+// Layers are named and filled with the same names display filters use. A field
+// a layer reports more than once becomes an array.
+
+use crate::packets::fields::{Field, FieldValue};
+use crate::packets::frame::Frame;
+use crate::packets::shared_objs::LayerData;
+use std::io::{self, Write};
+
+// Writes frames as they come, either as one JSON array or as newline delimited
+// JSON with an object per line, for piping into jq and the like
+pub struct JsonWriter<W: Write> {
+    writer: W,
+    ndjson: bool,
+    written: usize,
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(writer: W, ndjson: bool) -> JsonWriter<W> {
+        JsonWriter {
+            writer,
+            ndjson,
+            written: 0,
+        }
+    }
+
+    pub fn write(&mut self, frame: &Frame) -> io::Result<()> {
+        let separator = match (self.ndjson, self.written) {
+            (true, _) => "",
+            (false, 0) => "[\n",
+            (false, _) => ",\n",
+        };
+        write!(self.writer, "{separator}{}", frame_json(frame))?;
+        if self.ndjson {
+            writeln!(self.writer)?;
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    // closes the array, hands back the writer
+    pub fn finish(mut self) -> io::Result<W> {
+        match (self.ndjson, self.written) {
+            (true, _) => {}
+            (false, 0) => writeln!(self.writer, "[]")?,
+            (false, _) => writeln!(self.writer, "\n]")?,
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+// one frame as a single line object
+pub fn frame_json(frame: &Frame) -> String {
+    let layers = { frame.layers().iter() }
+        .filter_map(layer_json)
+        .collect::<Vec<String>>()
+        .join(",");
+    format!(
+        "{{\"id\":{},\"timestamp\":{},\"layers\":[{layers}]}}",
+        frame.id,
+        string(&frame.timestamp.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string()),
+    )
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+fn layer_json(layer: &LayerData) -> Option<String> {
+    let protocol = layer.protocol()?;
+
+    // repeated names are gathered up, keeping the order they first came in
+    let mut fields: Vec<(&str, Vec<FieldValue>)> = vec![];
+    for Field { name, value } in layer.fields() {
+        match fields.iter_mut().find(|(field, _)| *field == name) {
+            Some((_, values)) => values.push(value),
+            None => fields.push((name, vec![value])),
+        }
+    }
+    let fields = { fields.iter() }
+        .map(|(name, values)| {
+            let value = match values.as_slice() {
+                [value] => value_json(value),
+                values => {
+                    let values = values.iter().map(value_json).collect::<Vec<String>>();
+                    format!("[{}]", values.join(","))
+                }
+            };
+            format!("{}:{value}", string(name))
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+
+    Some(format!(
+        "{{\"protocol\":{},\"fields\":{{{fields}}}}}",
+        string(&protocol.filter_name())
+    ))
+}
+
+fn value_json(value: &FieldValue) -> String {
+    match value {
+        FieldValue::Int(value) => value.to_string(),
+        FieldValue::Bool(value) => value.to_string(),
+        value => string(&value.to_string()),
+    }
+}
+
+fn string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
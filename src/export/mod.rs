@@ -0,0 +1,3 @@
+// Getting dissected frames out of cnote in formats other tools read
+pub mod csv;
+pub mod json;
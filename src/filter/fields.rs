@@ -0,0 +1,72 @@
+use crate::packets::{dissectors, fields, fields::FieldKind, frame::Frame};
+
+// What a name in a filter refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldSchema {
+    Protocol,
+    Field(FieldKind),
+}
+
+pub(crate) fn lookup(name: &str) -> Result<FieldSchema, String> {
+    if PROTOCOLS.contains(&name) {
+        return Ok(FieldSchema::Protocol);
+    }
+    if let Some(kind) = fields::field_kind(name) {
+        return Ok(FieldSchema::Field(kind));
+    }
+    if { dissectors::protocol_names().iter() }.any(|protocol| protocol.to_lowercase() == name) {
+        return Ok(FieldSchema::Protocol);
+    }
+    Err(match suggestion(name) {
+        Some(close) => {
+            format!("\"{name}\" is neither a protocol nor a field, did you mean {close}?")
+        }
+        None => format!("\"{name}\" is neither a protocol nor a field"),
+    })
+}
+
+// `name` is lowercase
+pub(crate) fn has_protocol(frame: &Frame, name: &str) -> bool {
+    if name == "vlan" {
+        return !frame.field_values("vlan.id").is_empty();
+    }
+    { frame.layers().iter() }
+        .any(|layer| { layer.protocol() }.is_some_and(|p| p.filter_name() == name))
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+const PROTOCOLS: &[&str] = &[
+    "frame", "eth", "vlan", "sll", "null", "arp", "ip", "ipv6", "icmp", "icmpv6", "tcp", "udp",
+    "dns", "http", "tls",
+];
+
+// the closest known name, if one is only a typo away
+fn suggestion(name: &str) -> Option<&'static str> {
+    { PROTOCOLS.iter().copied() }
+        .chain(fields::known_fields().into_iter().map(|(field, _)| field))
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2 && *distance < name.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
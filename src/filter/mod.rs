@@ -0,0 +1,98 @@
+// Display filters, wireshark style expressions picking which captured frames
+// get shown, e.g.
+//
+//     ip.src == 10.0.0.1 && tcp.port in {80 443} && dns.qry.name contains "corp"
+//
+// A bare protocol or field name tests that it's there. Fields that occur more
+// than once in a frame (ip.addr, tcp.port, dns.a, ...) match when any of their
+// values does, except "!=" which needs all of them to differ.
+
+mod fields;
+mod parser;
+mod value;
+
+pub use parser::FilterError;
+
+use crate::packets::{fields::FieldValue, frame::Frame};
+use parser::{Comparison, Expr, SetItem};
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct DisplayFilter {
+    expr: Expr,
+    text: String,
+}
+
+impl DisplayFilter {
+    pub fn parse(text: &str) -> Result<DisplayFilter, FilterError> {
+        Ok(DisplayFilter {
+            expr: parser::parse(text)?,
+            text: text.trim().to_owned(),
+        })
+    }
+
+    pub fn matches(&self, frame: &Frame) -> bool {
+        evaluate(&self.expr, frame)
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl fmt::Display for DisplayFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+fn evaluate(expr: &Expr, frame: &Frame) -> bool {
+    match expr {
+        Expr::And(left, right) => evaluate(left, frame) && evaluate(right, frame),
+        Expr::Or(left, right) => evaluate(left, frame) || evaluate(right, frame),
+        Expr::Not(inner) => !evaluate(inner, frame),
+        Expr::Protocol(name) => fields::has_protocol(frame, name),
+        Expr::Exists(name) => !frame.field_values(name).is_empty(),
+        Expr::Compare(name, Comparison::NotEqual, operand) => {
+            let values = frame.field_values(name);
+            !values.is_empty() && !values.iter().any(|value| operand.equals(value))
+        }
+        Expr::Compare(name, comparison, operand) => {
+            { frame.field_values(name).iter() }.any(|value| match comparison {
+                Comparison::Equal => operand.equals(value),
+                Comparison::Contains => operand.contained_in(value),
+                Comparison::Less => operand.compare(value) == Some(Ordering::Less),
+                Comparison::LessOrEqual => {
+                    matches!(
+                        operand.compare(value),
+                        Some(Ordering::Less | Ordering::Equal)
+                    )
+                }
+                Comparison::Greater => operand.compare(value) == Some(Ordering::Greater),
+                Comparison::GreaterOrEqual => matches!(
+                    operand.compare(value),
+                    Some(Ordering::Greater | Ordering::Equal)
+                ),
+                Comparison::NotEqual => unreachable!("handled above"),
+            })
+        }
+        Expr::In(name, items) => { frame.field_values(name).iter() }.any(|value| {
+            items.iter().any(|item| match (item, value) {
+                (SetItem::Single(operand), value) => operand.equals(value),
+                (SetItem::Range(low, high), FieldValue::Int(value)) => {
+                    (low..=high).contains(&value)
+                }
+                (SetItem::Range(..), _) => false,
+            })
+        }),
+    }
+}
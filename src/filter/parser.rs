@@ -0,0 +1,468 @@
+use super::fields::{self, FieldSchema};
+use super::value::Operand;
+use crate::packets::fields::FieldKind;
+use std::fmt;
+
+// What went wrong and the column (from 1) it went wrong at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+    pub message: String,
+    pub column: usize,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (column {})", self.message, self.column)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Contains,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum SetItem {
+    Single(Operand),
+    // inclusive, integers only
+    Range(u64, u64),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    // lowercase protocol name
+    Protocol(String),
+    Exists(String),
+    Compare(String, Comparison, Operand),
+    In(String, Vec<SetItem>),
+}
+
+pub(crate) fn parse(text: &str) -> Result<Expr, FilterError> {
+    let tokens = lex(text)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        end: text.chars().count(),
+    };
+    let expr = parser.or()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(token.error(format!(
+            "unexpected {} after a complete expression, is an \"&&\" or \"||\" missing?",
+            token.kind
+        ))),
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    Comma,
+    DotDot,
+    And,
+    Or,
+    Not,
+    Comparison(Comparison),
+    In,
+    Matches,
+    Str(String),
+    Word(String),
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::LeftParen => write!(f, "\"(\""),
+            TokenKind::RightParen => write!(f, "\")\""),
+            TokenKind::LeftBrace => write!(f, "\"{{\""),
+            TokenKind::RightBrace => write!(f, "\"}}\""),
+            TokenKind::Comma => write!(f, "\",\""),
+            TokenKind::DotDot => write!(f, "\"..\""),
+            TokenKind::And => write!(f, "\"&&\""),
+            TokenKind::Or => write!(f, "\"||\""),
+            TokenKind::Not => write!(f, "\"!\""),
+            TokenKind::Comparison(comparison) => {
+                write!(f, "\"{}\"", comparison_symbol(*comparison))
+            }
+            TokenKind::In => write!(f, "\"in\""),
+            TokenKind::Matches => write!(f, "\"matches\""),
+            TokenKind::Str(text) => write!(f, "the string \"{text}\""),
+            TokenKind::Word(text) => write!(f, "\"{text}\""),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    // character offset into the filter text
+    start: usize,
+}
+
+impl Token {
+    fn error(&self, message: String) -> FilterError {
+        FilterError {
+            message,
+            column: self.start + 1,
+        }
+    }
+}
+
+fn comparison_symbol(comparison: Comparison) -> &'static str {
+    match comparison {
+        Comparison::Equal => "==",
+        Comparison::NotEqual => "!=",
+        Comparison::Less => "<",
+        Comparison::LessOrEqual => "<=",
+        Comparison::Greater => ">",
+        Comparison::GreaterOrEqual => ">=",
+        Comparison::Contains => "contains",
+    }
+}
+
+// field names, numbers, addresses and bare strings
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | ':' | '-' | '/')
+}
+
+fn lex(text: &str) -> Result<Vec<Token>, FilterError> {
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let error = |message: String| FilterError {
+            message,
+            column: start + 1,
+        };
+        let next = chars.get(i + 1).copied();
+        let (kind, length) = match (chars[i], next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (TokenKind::LeftParen, 1),
+            (')', _) => (TokenKind::RightParen, 1),
+            ('{', _) => (TokenKind::LeftBrace, 1),
+            ('}', _) => (TokenKind::RightBrace, 1),
+            (',', _) => (TokenKind::Comma, 1),
+            ('.', Some('.')) => (TokenKind::DotDot, 2),
+            ('&', Some('&')) => (TokenKind::And, 2),
+            ('|', Some('|')) => (TokenKind::Or, 2),
+            ('=', Some('=')) => (TokenKind::Comparison(Comparison::Equal), 2),
+            ('!', Some('=')) => (TokenKind::Comparison(Comparison::NotEqual), 2),
+            ('<', Some('=')) => (TokenKind::Comparison(Comparison::LessOrEqual), 2),
+            ('>', Some('=')) => (TokenKind::Comparison(Comparison::GreaterOrEqual), 2),
+            ('<', _) => (TokenKind::Comparison(Comparison::Less), 1),
+            ('>', _) => (TokenKind::Comparison(Comparison::Greater), 1),
+            ('!', _) => (TokenKind::Not, 1),
+            ('~', _) => (TokenKind::Matches, 1),
+            ('=', _) => return Err(error("\"=\" isn't an operator, use \"==\"".to_owned())),
+            ('&', _) => return Err(error("\"&\" isn't an operator, use \"&&\"".to_owned())),
+            ('|', _) => return Err(error("\"|\" isn't an operator, use \"||\"".to_owned())),
+            ('"', _) => {
+                let mut string = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(error("this string is never closed".to_owned())),
+                        Some('"') => break,
+                        Some('\\') => {
+                            let escaped = match chars.get(j + 1) {
+                                Some('n') => '\n',
+                                Some('r') => '\r',
+                                Some('t') => '\t',
+                                Some(c) => *c,
+                                None => {
+                                    return Err(error("this string is never closed".to_owned()))
+                                }
+                            };
+                            string.push(escaped);
+                            j += 2;
+                        }
+                        Some(c) => {
+                            string.push(*c);
+                            j += 1;
+                        }
+                    }
+                }
+                (TokenKind::Str(string), j + 1 - i)
+            }
+            (c, _) if is_word_char(c) => {
+                let mut j = i;
+                // ".." ends a word, it's the range in a set
+                while j < chars.len()
+                    && is_word_char(chars[j])
+                    && !(chars[j] == '.' && chars.get(j + 1) == Some(&'.'))
+                {
+                    j += 1;
+                }
+                let word = chars[i..j].iter().collect::<String>();
+                let kind = match word.to_ascii_lowercase().as_str() {
+                    "and" => TokenKind::And,
+                    "or" => TokenKind::Or,
+                    "not" => TokenKind::Not,
+                    "in" => TokenKind::In,
+                    "contains" => TokenKind::Comparison(Comparison::Contains),
+                    "matches" => TokenKind::Matches,
+                    "eq" => TokenKind::Comparison(Comparison::Equal),
+                    "ne" => TokenKind::Comparison(Comparison::NotEqual),
+                    "lt" => TokenKind::Comparison(Comparison::Less),
+                    "le" => TokenKind::Comparison(Comparison::LessOrEqual),
+                    "gt" => TokenKind::Comparison(Comparison::Greater),
+                    "ge" => TokenKind::Comparison(Comparison::GreaterOrEqual),
+                    _ => TokenKind::Word(word),
+                };
+                (kind, j - i)
+            }
+            (c, _) => return Err(error(format!("unexpected character '{c}'"))),
+        };
+        tokens.push(Token { kind, start });
+        i += length;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    // length of the text, for errors at the very end
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        let found = self.peek().is_some_and(|token| token.kind == *kind);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn end_error(&self, expected: &str) -> FilterError {
+        FilterError {
+            message: format!("the filter ends where {expected} was expected"),
+            column: self.end + 1,
+        }
+    }
+
+    // || binds loosest, then &&, then !
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.eat(&TokenKind::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.not()?;
+        while self.eat(&TokenKind::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, FilterError> {
+        match self.eat(&TokenKind::Not) {
+            true => Ok(Expr::Not(Box::new(self.not()?))),
+            false => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, FilterError> {
+        let token = self
+            .next()
+            .ok_or_else(|| self.end_error("a field or protocol"))?;
+        let name = match token.kind {
+            TokenKind::LeftParen => {
+                let expr = self.or()?;
+                return match self.next() {
+                    Some(Token {
+                        kind: TokenKind::RightParen,
+                        ..
+                    }) => Ok(expr),
+                    Some(other) => Err(other.error(format!(
+                        "expected \")\" to close the \"(\" at column {}, found {}",
+                        token.start + 1,
+                        other.kind
+                    ))),
+                    None => Err(token.error("this \"(\" is never closed".to_owned())),
+                };
+            }
+            TokenKind::Word(ref name) => name.to_ascii_lowercase(),
+            ref kind => {
+                return Err(token.error(format!("expected a field or protocol, found {kind}")))
+            }
+        };
+
+        let schema = fields::lookup(&name).map_err(|message| token.error(message))?;
+        let operator = self.peek().map(|next| next.kind.clone());
+        let kind = match (schema, operator) {
+            (FieldSchema::Protocol, Some(TokenKind::Comparison(_) | TokenKind::In)) => {
+                let next = self.next().unwrap();
+                return Err(next.error(format!(
+                    "{name} is a protocol, it can only be tested for on its own"
+                )));
+            }
+            (FieldSchema::Protocol, _) => return Ok(Expr::Protocol(name)),
+            (FieldSchema::Field(kind), _) => kind,
+        };
+        match self.peek().map(|next| next.kind.clone()) {
+            Some(TokenKind::Matches) => {
+                let next = self.next().unwrap();
+                Err(next.error(
+                    "regular expressions aren't supported, try \"contains\" instead".to_owned(),
+                ))
+            }
+            Some(TokenKind::Comparison(comparison)) => {
+                let operator = self.next().unwrap();
+                check_comparison(&name, kind, comparison).map_err(|m| operator.error(m))?;
+                let operand = self.literal(kind, &name, &operator)?;
+                Ok(Expr::Compare(name, comparison, operand))
+            }
+            Some(TokenKind::In) => {
+                let operator = self.next().unwrap();
+                let items = self.set(kind, &name, &operator)?;
+                Ok(Expr::In(name, items))
+            }
+            _ => Ok(Expr::Exists(name)),
+        }
+    }
+
+    fn literal(
+        &mut self,
+        kind: FieldKind,
+        field: &str,
+        after: &Token,
+    ) -> Result<Operand, FilterError> {
+        let token = self
+            .next()
+            .ok_or_else(|| self.end_error(&format!("a value after {}", after.kind)))?;
+        let (text, quoted) = match token.kind {
+            TokenKind::Word(ref text) => (text.clone(), false),
+            TokenKind::Str(ref text) => (text.clone(), true),
+            ref kind => {
+                return Err(token.error(format!(
+                    "expected a value after {}, found {kind}",
+                    after.kind
+                )))
+            }
+        };
+        Operand::parse(kind, &text, quoted).ok_or_else(|| {
+            token.error(format!(
+                "\"{text}\" isn't a valid value for {field}, which is {kind}"
+            ))
+        })
+    }
+
+    // {80 443 8000..8080}, commas between items are optional
+    fn set(
+        &mut self,
+        kind: FieldKind,
+        field: &str,
+        after: &Token,
+    ) -> Result<Vec<SetItem>, FilterError> {
+        match self.next() {
+            Some(Token {
+                kind: TokenKind::LeftBrace,
+                ..
+            }) => {}
+            Some(other) => {
+                return Err(other.error(format!(
+                    "expected \"{{\" to start a set after \"in\", found {}",
+                    other.kind
+                )))
+            }
+            None => return Err(self.end_error("a set like {80 443}")),
+        }
+
+        let mut items = vec![];
+        loop {
+            match self.peek() {
+                Some(Token {
+                    kind: TokenKind::RightBrace,
+                    ..
+                }) => {
+                    let close = self.next().unwrap();
+                    if items.is_empty() {
+                        return Err(close.error("the set is empty".to_owned()));
+                    }
+                    return Ok(items);
+                }
+                None => return Err(self.end_error("\"}\" to close the set")),
+                _ => {}
+            }
+
+            let start = self.peek().cloned().unwrap();
+            let low = self.literal(kind, field, after)?;
+            if !self.eat(&TokenKind::DotDot) {
+                items.push(SetItem::Single(low));
+                self.eat(&TokenKind::Comma);
+                continue;
+            }
+            let high = self.literal(kind, field, after)?;
+            match (low, high) {
+                (Operand::Int(low), Operand::Int(high)) if low > high => {
+                    return Err(start.error(format!(
+                        "the range {low}..{high} is empty, the lower bound goes first"
+                    )))
+                }
+                (Operand::Int(low), Operand::Int(high)) => items.push(SetItem::Range(low, high)),
+                _ => {
+                    return Err(start.error(format!(
+                        "ranges only work on integer fields, {field} isn't one"
+                    )))
+                }
+            }
+            self.eat(&TokenKind::Comma);
+        }
+    }
+}
+
+fn check_comparison(field: &str, kind: FieldKind, comparison: Comparison) -> Result<(), String> {
+    match (kind, comparison) {
+        (FieldKind::Str | FieldKind::Bytes, Comparison::Contains) => Ok(()),
+        (_, Comparison::Contains) => Err(format!(
+            "{field} is {kind}, \"contains\" only works on strings and bytes"
+        )),
+        (FieldKind::Bool, Comparison::Equal | Comparison::NotEqual) => Ok(()),
+        (FieldKind::Bool, comparison) => Err(format!(
+            "{field} is {kind}, it can't be compared with \"{}\"",
+            comparison_symbol(comparison)
+        )),
+        _ => Ok(()),
+    }
+}
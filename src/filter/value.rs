@@ -0,0 +1,113 @@
+use crate::packets::fields::{parse_hex_bytes, FieldKind, FieldValue};
+use std::cmp::Ordering;
+use std::net::IpAddr;
+
+// A literal from the filter text, read as the kind of field it's compared with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Operand {
+    Int(u64),
+    Bool(bool),
+    Str(String),
+    Bytes(Vec<u8>),
+    // network and prefix length, a plain address is a full length prefix
+    Ip(IpAddr, u8),
+    Mac([u8; 6]),
+}
+
+impl Operand {
+    // `quoted` literals were written as "strings", for bytes that means the
+    // string's own bytes rather than hex
+    pub(crate) fn parse(kind: FieldKind, text: &str, quoted: bool) -> Option<Operand> {
+        match kind {
+            FieldKind::Int => parse_int(text).map(Operand::Int),
+            FieldKind::Bool => match text.to_ascii_lowercase().as_str() {
+                "1" | "true" => Some(Operand::Bool(true)),
+                "0" | "false" => Some(Operand::Bool(false)),
+                _ => None,
+            },
+            FieldKind::Str => Some(Operand::Str(text.to_owned())),
+            FieldKind::Bytes if quoted => Some(Operand::Bytes(text.as_bytes().to_vec())),
+            FieldKind::Bytes => parse_hex_bytes(text).map(Operand::Bytes),
+            FieldKind::Ip => parse_network(text),
+            FieldKind::Mac => match FieldValue::mac(text) {
+                Some(FieldValue::Mac(mac)) => Some(Operand::Mac(mac)),
+                _ => None,
+            },
+        }
+    }
+
+    pub(crate) fn equals(&self, value: &FieldValue) -> bool {
+        match (value, self) {
+            (FieldValue::Ip(address), Operand::Ip(network, prefix)) => {
+                in_network(*address, *network, *prefix)
+            }
+            _ => self.compare(value) == Some(Ordering::Equal),
+        }
+    }
+
+    // how `value` orders against this operand, None when they don't compare
+    pub(crate) fn compare(&self, value: &FieldValue) -> Option<Ordering> {
+        match (value, self) {
+            (FieldValue::Int(a), Operand::Int(b)) => Some(a.cmp(b)),
+            (FieldValue::Bool(a), Operand::Bool(b)) => Some(a.cmp(b)),
+            (FieldValue::Str(a), Operand::Str(b)) => Some(a.as_str().cmp(b)),
+            (FieldValue::Bytes(a), Operand::Bytes(b)) => Some(a.cmp(b)),
+            (FieldValue::Ip(a), Operand::Ip(b, _)) => Some(a.cmp(b)),
+            (FieldValue::Mac(a), Operand::Mac(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn contained_in(&self, value: &FieldValue) -> bool {
+        match (value, self) {
+            (FieldValue::Str(haystack), Operand::Str(needle)) => haystack.contains(needle.as_str()),
+            (FieldValue::Bytes(haystack), Operand::Bytes(needle)) => {
+                needle.is_empty() || haystack.windows(needle.len()).any(|w| w == needle)
+            }
+            _ => false,
+        }
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+fn parse_int(text: &str) -> Option<u64> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+// 10.0.0.1, 10.0.0.0/8, fe80::/10
+fn parse_network(text: &str) -> Option<Operand> {
+    let (address, prefix) = match text.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix.parse::<u8>().ok()?)),
+        None => (text, None),
+    };
+    let address = address.parse::<IpAddr>().ok()?;
+    let max = match address {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    };
+    match prefix.unwrap_or(max) {
+        prefix if prefix <= max => Some(Operand::Ip(address, prefix)),
+        _ => None,
+    }
+}
+
+fn in_network(address: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    let (address, network, bits) = match (address, network) {
+        (IpAddr::V4(a), IpAddr::V4(n)) => (u32::from(a) as u128, u32::from(n) as u128, 32),
+        (IpAddr::V6(a), IpAddr::V6(n)) => (u128::from(a), u128::from(n), 128),
+        _ => return false,
+    };
+    let shift = bits - prefix as u32;
+    // a shift by the full width would overflow, a /0 matches everything
+    shift >= bits || (address >> shift) == (network >> shift)
+}
@@ -1,4 +1,5 @@
-use crate::gui::pane_tree::{create_tree, Pane, TreeBehavior};
+use crate::gui::pane_tree::{self, create_tree, Pane, TreeBehavior};
+use crate::gui::panes::drill_down::ByteSelection;
 use crate::sniffer::Sniffer;
 use eframe::Frame;
 use egui::{ComboBox, Context};
@@ -12,8 +13,14 @@ pub struct Capture {
     label: Option<String>,
     device_none_modal: bool,
     show_device_modal: bool,
+    capture_filter: String,
+    capture_error: Option<String>,
     tree: egui_tiles::Tree<Pane>,
     selected_packet: Option<i32>,
+    selection: ByteSelection,
+    follow_stream: Option<u32>,
+    display_filter: Option<String>,
+    redissect: bool,
 }
 
 impl eframe::App for Capture {
@@ -52,14 +59,53 @@ impl eframe::App for Capture {
                     ui.label(format!("selected device: {}", device));
                 }
 
+                ui.label("capture filter:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.capture_filter)
+                        .hint_text("e.g. tcp port 443")
+                        .desired_width(200.0),
+                );
+
                 if ui.button("Upload pcap").clicked() {
                     if let Some(path) = rfd::FileDialog::new()
-                        .add_filter("Packet Capture Files", &["pcap", "cap"])
+                        .add_filter("Packet Capture Files", &["pcap", "pcapng", "cap"])
                         .pick_file()
                     {
                         self.start(Some(path.to_string_lossy().to_string()))
                     }
                 }
+
+                let can_save = !self.running && !self.sniffer.captured_packets.is_empty();
+                if ui
+                    .add_enabled(can_save, egui::Button::new("Save as"))
+                    .clicked()
+                {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("pcapng", &["pcapng"])
+                        .add_filter("pcap", &["pcap"])
+                        .set_file_name("capture.pcapng")
+                        .save_file()
+                    {
+                        if let Err(e) = self.sniffer.save(&path) {
+                            self.capture_error = Some(format!("could not save capture: {e}"));
+                        }
+                    }
+                }
+                if ui
+                    .add_enabled(can_save, egui::Button::new("Export as JSON"))
+                    .clicked()
+                {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("JSON", &["json"])
+                        .add_filter("newline delimited JSON", &["ndjson", "jsonl"])
+                        .set_file_name("capture.json")
+                        .save_file()
+                    {
+                        if let Err(e) = self.sniffer.export_json(&path) {
+                            self.capture_error = Some(format!("could not export capture: {e}"));
+                        }
+                    }
+                }
                 if let Some(ref label) = self.label {
                     ui.label(label);
                 }
@@ -101,15 +147,48 @@ impl eframe::App for Capture {
             .open(&mut self.device_none_modal)
             .show(ctx, |ui| ui.label("Please select a device to run capture"));
 
+        let mut show_capture_error = self.capture_error.is_some();
+        egui::Window::new("Capture Error")
+            .open(&mut show_capture_error)
+            .show(ctx, |ui| {
+                if let Some(ref error) = self.capture_error {
+                    ui.label(error);
+                }
+            });
+        if !show_capture_error {
+            self.capture_error = None;
+        }
+
+        let selected_packet = self.selected_packet;
         egui::CentralPanel::default().show(ctx, |ui| {
             let mut behavior = TreeBehavior {
                 captured_packets: &self.sniffer.captured_packets,
+                generation: self.sniffer.generation,
+                changed_packets: &self.sniffer.changed_packets,
+                tcp_streams: self.sniffer.analyzer.tcp_streams(),
+                follow_stream: &mut self.follow_stream,
+                display_filter: &mut self.display_filter,
+                redissect: &mut self.redissect,
                 drilldown: "",
                 payload: &[],
                 selected_packet: &mut self.selected_packet,
+                selection: &mut self.selection,
             };
             self.tree.ui(&mut behavior, ui);
         });
+        if self.selected_packet != selected_packet {
+            self.selection = ByteSelection::default();
+        }
+        if let Some(stream) = self.follow_stream.take() {
+            pane_tree::follow_stream(&mut self.tree, stream);
+        }
+        if let Some(filter) = self.display_filter.take() {
+            pane_tree::filter_packets(&mut self.tree, &filter);
+        }
+        if std::mem::take(&mut self.redissect) {
+            self.sniffer.redissect();
+            self.selection = ByteSelection::default();
+        }
     }
 }
 
@@ -125,27 +204,39 @@ impl Capture {
             sniffer: Default::default(),
             tree: create_tree(),
             selected_packet: None,
+            selection: ByteSelection::default(),
+            follow_stream: None,
+            display_filter: None,
+            redissect: false,
             device: None,
             show_device_modal: false,
             device_none_modal: false,
+            capture_filter: String::new(),
+            capture_error: None,
             label: None,
         }
     }
     pub fn get_packets(&mut self) {
-        if let Some(receiver) = self.sniffer.receiver.as_mut() {
-            self.sniffer.captured_packets.extend(receiver.try_iter());
-        }
+        self.sniffer.get_packets();
     }
     pub fn start(&mut self, file: Option<String>) {
-        self.sniffer.captured_packets = vec![];
-        if let Some(file) = file {
+        let filter = Some(self.capture_filter.trim()).filter(|f| !f.is_empty());
+        let result = if let Some(file) = file {
             self.label = Some(format!("file: {}", file));
-            self.sniffer.from_file(file);
+            self.sniffer.from_file(file, filter)
         } else if let Some(ref device) = self.device {
             self.label = Some("running...".to_string());
-            self.sniffer.capture(device);
+            self.sniffer.capture(device, filter)
+        } else {
+            return;
+        };
+        match result {
+            Ok(()) => self.running = true,
+            Err(e) => {
+                self.label = None;
+                self.capture_error = Some(e.to_string());
+            }
         }
-        self.running = true;
     }
     pub fn stop(&mut self) {
         self.label = None;
@@ -1,14 +1,32 @@
+use crate::analysis::tcp_stream::TcpStream;
 use crate::gui;
-use crate::gui::panes::packet_table::PacketTable;
-use crate::packets::data_link::ethernet::EthernetFrame;
+use crate::gui::panes::{
+    arp_table::ArpTablePane, conversations::ConversationsPane, drill_down::ByteSelection,
+    endpoints::EndpointsPane, follow_stream::FollowStreamPane, packet_table::PacketTable,
+    payload::PayloadPane,
+};
+use crate::packets::frame::Frame;
 use egui::{Sense, Ui, WidgetText};
-use egui_tiles::{Behavior, TileId, UiResponse};
+use egui_tiles::{Behavior, Container, Tile, TileId, UiResponse};
 
 pub struct TreeBehavior<'a> {
-    pub captured_packets: &'a [EthernetFrame],
+    pub captured_packets: &'a [Frame],
+    // `Sniffer::generation`, changes when a new capture replaces the packets
+    pub generation: u32,
+    // `Sniffer::changed_packets`
+    pub changed_packets: &'a [i32],
+    pub tcp_streams: &'a [TcpStream],
+    // set when a pane asks for a stream to be followed
+    pub follow_stream: &'a mut Option<u32>,
+    // set when a pane asks for the packet table to be filtered
+    pub display_filter: &'a mut Option<String>,
+    // set when the decode as rules changed and the capture needs parsing again
+    pub redissect: &'a mut bool,
     pub drilldown: &'a str,
     pub payload: &'a [u8],
     pub selected_packet: &'a mut Option<i32>,
+    // shared by the drill down and payload panes
+    pub selection: &'a mut ByteSelection,
 }
 #[derive(Clone)]
 pub struct Pane {
@@ -20,8 +38,12 @@ pub struct Pane {
 pub enum Module {
     Packets(PacketTable),
     PacketDrill,
-    Payload,
+    Payload(PayloadPane),
     PacketGraph,
+    ArpTable(ArpTablePane),
+    Conversations(ConversationsPane),
+    Endpoints(EndpointsPane),
+    FollowStream(FollowStreamPane),
 }
 
 impl<'a> Behavior<Pane> for TreeBehavior<'a> {
@@ -32,23 +54,58 @@ impl<'a> Behavior<Pane> for TreeBehavior<'a> {
 
         match pane.module {
             Module::Packets(ref mut table) => {
-                table.render(ui, self.captured_packets, self.selected_packet);
+                table.render(
+                    ui,
+                    self.captured_packets,
+                    self.generation,
+                    self.changed_packets,
+                    self.selected_packet,
+                    self.follow_stream,
+                    self.redissect,
+                );
             }
             Module::PacketDrill => {
                 if let Some(packet) =
                     { *self.selected_packet }.and_then(|i| self.captured_packets.get(i as usize))
                 {
-                    gui::panes::drill_down::drill_ui(ui, packet);
+                    gui::panes::drill_down::drill_ui(ui, packet, self.selection);
                 }
             }
-            Module::Payload => {
+            Module::Payload(ref mut payload) => {
                 if let Some(packet) =
                     { *self.selected_packet }.and_then(|i| self.captured_packets.get(i as usize))
                 {
-                    gui::panes::payload::payload_ui(ui, packet);
+                    payload.render(ui, packet, self.selection);
                 }
             }
             Module::PacketGraph => gui::panes::graph::graph_ui(ui, self.captured_packets),
+            Module::ArpTable(ref mut table) => {
+                table.render(
+                    ui,
+                    self.captured_packets,
+                    self.generation,
+                    self.selected_packet,
+                );
+            }
+            Module::Conversations(ref mut conversations) => {
+                conversations.render(
+                    ui,
+                    self.captured_packets,
+                    self.generation,
+                    self.display_filter,
+                );
+            }
+            Module::Endpoints(ref mut endpoints) => {
+                endpoints.render(
+                    ui,
+                    self.captured_packets,
+                    self.generation,
+                    self.display_filter,
+                );
+            }
+            Module::FollowStream(ref mut follow) => {
+                follow.render(ui, self.tcp_streams, self.selected_packet);
+            }
         }
 
         if dragged {
@@ -61,6 +118,13 @@ impl<'a> Behavior<Pane> for TreeBehavior<'a> {
     fn tab_title_for_pane(&mut self, pane: &Pane) -> WidgetText {
         pane.title.to_owned().into()
     }
+
+    fn retain_pane(&mut self, pane: &Pane) -> bool {
+        match pane.module {
+            Module::FollowStream(ref follow) => !follow.is_closed(),
+            _ => true,
+        }
+    }
 }
 
 pub fn create_tree() -> egui_tiles::Tree<Pane> {
@@ -81,7 +145,19 @@ pub fn create_tree() -> egui_tiles::Tree<Pane> {
         }),
         tiles.insert_pane(Pane {
             title: "Payload".into(),
-            module: Module::Payload,
+            module: Module::Payload(PayloadPane::default()),
+        }),
+        tiles.insert_pane(Pane {
+            title: "ARP".into(),
+            module: Module::ArpTable(ArpTablePane::default()),
+        }),
+        tiles.insert_pane(Pane {
+            title: "Conversations".into(),
+            module: Module::Conversations(ConversationsPane::default()),
+        }),
+        tiles.insert_pane(Pane {
+            title: "Endpoints".into(),
+            module: Module::Endpoints(EndpointsPane::default()),
         }),
     ];
 
@@ -89,3 +165,56 @@ pub fn create_tree() -> egui_tiles::Tree<Pane> {
 
     egui_tiles::Tree::new(root, tiles)
 }
+
+// switches to the stream's pane, opening a new tab for it if there isn't one
+pub fn follow_stream(tree: &mut egui_tiles::Tree<Pane>, stream: u32) {
+    let is_stream_pane = |tile: &Tile<Pane>| match tile {
+        Tile::Pane(Pane {
+            module: Module::FollowStream(follow),
+            ..
+        }) => follow.stream == stream,
+        _ => false,
+    };
+    if tree.make_active(is_stream_pane) {
+        return;
+    }
+
+    let tile = tree.tiles.insert_pane(Pane {
+        title: format!("TCP Stream {stream}"),
+        module: Module::FollowStream(FollowStreamPane::new(stream)),
+    });
+    match tree.root.and_then(|root| tree.tiles.get_mut(root)) {
+        Some(Tile::Container(Container::Tabs(tabs))) => {
+            tabs.add_child(tile);
+            tabs.set_active(tile);
+        }
+        Some(Tile::Container(container)) => container.add_child(tile),
+        // a lone pane (or nothing) at the root, put it in tabs alongside the new one
+        _ => {
+            let children = tree.root.into_iter().chain([tile]).collect();
+            tree.root = Some(tree.tiles.insert_tab_tile(children));
+        }
+    }
+}
+
+// filters the packet table and switches to it
+pub fn filter_packets(tree: &mut egui_tiles::Tree<Pane>, filter: &str) {
+    for tile in tree.tiles.tiles_mut() {
+        if let Tile::Pane(Pane {
+            module: Module::Packets(table),
+            ..
+        }) = tile
+        {
+            table.set_filter(filter);
+        }
+    }
+    tree.make_active(|tile| {
+        matches!(
+            tile,
+            Tile::Pane(Pane {
+                module: Module::Packets(_),
+                ..
+            })
+        )
+    });
+}
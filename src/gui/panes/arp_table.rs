@@ -0,0 +1,76 @@
+use crate::packets::frame::Frame;
+use crate::packets::internet::arp::ArpTable;
+use egui::{Color32, RichText};
+use egui_extras::{Column, TableBuilder};
+
+#[derive(Default, Clone)]
+pub struct ArpTablePane {
+    table: ArpTable,
+}
+
+impl ArpTablePane {
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        data: &[Frame],
+        generation: u32,
+        selected_packet: &mut Option<i32>,
+    ) {
+        self.table.update(data, generation);
+
+        let rows = { self.table.bindings.iter() }
+            .flat_map(|(ip, macs)| macs.iter().map(move |(mac, binding)| (ip, mac, binding)))
+            .collect::<Vec<_>>();
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .auto_shrink([false, true])
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .columns(Column::auto(), 5)
+            .min_scrolled_height(0.0)
+            .resizable(true)
+            .header(20.0, |mut header| {
+                for title in ["ip", "mac", "frames", "first seen", "status"] {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(18.0, rows.len(), |index, mut row| {
+                    let (ip, mac, binding) = rows[index];
+                    let conflicted = self.table.is_conflicted(ip);
+                    let text = |text: &str| {
+                        let text = RichText::new(text);
+                        if conflicted {
+                            text.color(Color32::RED)
+                        } else {
+                            text
+                        }
+                    };
+
+                    row.col(|ui| {
+                        ui.label(text(ip));
+                    });
+                    row.col(|ui| {
+                        ui.label(text(mac));
+                    });
+                    row.col(|ui| {
+                        ui.label(binding.frames.to_string());
+                    });
+                    row.col(|ui| {
+                        if ui.button(binding.first_seen.to_string()).clicked() {
+                            *selected_packet = Some(binding.first_seen);
+                        }
+                    });
+                    row.col(|ui| {
+                        if conflicted {
+                            ui.label(text("claimed by multiple MACs"));
+                        } else {
+                            ui.label("ok");
+                        }
+                    });
+                });
+            });
+    }
+}
@@ -0,0 +1,102 @@
+use crate::analysis::conversations::{Conversation, Conversations, Level};
+use crate::packets::frame::Frame;
+use egui_extras::{Column, TableBuilder};
+
+#[derive(Default, Clone)]
+pub struct ConversationsPane {
+    level: Level,
+    table: Conversations,
+}
+
+impl ConversationsPane {
+    // clicking a conversation puts a filter for it in `display_filter`
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        data: &[Frame],
+        generation: u32,
+        display_filter: &mut Option<String>,
+    ) {
+        self.table.update(data, generation);
+
+        ui.horizontal(|ui| {
+            for level in Level::ALL {
+                let count = self.table.at_level(level).count();
+                ui.selectable_value(&mut self.level, level, format!("{level} ({count})"));
+            }
+        });
+
+        let rows = self
+            .table
+            .at_level(self.level)
+            .collect::<Vec<&Conversation>>();
+        let tcp = self.level == Level::Tcp;
+        let mut titles = vec![
+            "address a",
+            "address b",
+            "packets",
+            "bytes",
+            "packets a → b",
+            "bytes a → b",
+            "packets b → a",
+            "bytes b → a",
+            "start",
+            "duration",
+        ];
+        if tcp {
+            titles.push("state");
+        }
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .auto_shrink([false, true])
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .columns(Column::auto(), titles.len())
+            .min_scrolled_height(0.0)
+            .resizable(true)
+            .header(20.0, |mut header| {
+                for title in &titles {
+                    header.col(|ui| {
+                        ui.strong(*title);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(18.0, rows.len(), |index, mut row| {
+                    let conversation = rows[index];
+                    let duration = conversation.duration().num_microseconds().unwrap_or(0);
+                    let mut cells = vec![
+                        conversation.a.to_string(),
+                        conversation.b.to_string(),
+                        conversation.packets().to_string(),
+                        conversation.bytes().to_string(),
+                        conversation.packets_a_to_b.to_string(),
+                        conversation.bytes_a_to_b.to_string(),
+                        conversation.packets_b_to_a.to_string(),
+                        conversation.bytes_b_to_a.to_string(),
+                        conversation
+                            .start
+                            .format("%Y-%m-%d %H:%M:%S%.6f")
+                            .to_string(),
+                        format!("{:.6}", duration as f64 / 1_000_000.0),
+                    ];
+                    if tcp {
+                        cells.push(
+                            { conversation.tcp_state }
+                                .map(|state| state.to_string())
+                                .unwrap_or_default(),
+                        );
+                    }
+                    for text in cells {
+                        row.col(|ui| {
+                            let response =
+                                ui.button(text).on_hover_text("show in the packet table");
+                            if response.clicked() {
+                                *display_filter = Some(conversation.filter());
+                            }
+                        });
+                    }
+                });
+            });
+    }
+}
@@ -1,22 +1,92 @@
-use crate::packets::data_link::ethernet::EthernetFrame;
-use crate::packets::packet_traits::Describable;
-use egui::{CollapsingHeader, Ui};
-
-pub fn drill_ui(ui: &mut Ui, packet: &EthernetFrame) {
-    let drill_down = packet.get_long();
-    ui.vertical(|ui| {
-        for (key, value) in &drill_down {
-            accordion(ui, &key.to_string(), |ui| {
-                ui.label(value);
-            });
-        }
-    });
+use crate::packets::frame::Frame;
+use crate::packets::tree::TreeNode;
+use egui::collapsing_header::CollapsingState;
+use egui::{Align, ScrollArea, Ui};
+use std::ops::Range;
+
+// The node picked in the drill down, or the one holding the byte picked in the
+// payload pane. Both panes highlight its bytes.
+#[derive(Debug, Clone, Default)]
+pub struct ByteSelection {
+    // indices down the tree, empty when nothing is picked
+    pub path: Vec<usize>,
+    pub range: Option<Range<usize>>,
+    // set by whichever pane didn't make the pick, so the other scrolls to it
+    pub reveal_node: bool,
+    pub reveal_bytes: bool,
+}
+
+impl ByteSelection {
+    pub fn select(&mut self, tree: &[TreeNode], path: Vec<usize>) {
+        self.range = TreeNode::find(tree, &path).and_then(|node| node.range.clone());
+        self.path = path;
+    }
 }
 
-fn accordion(ui: &mut Ui, title: &str, content: impl FnOnce(&mut Ui)) {
-    CollapsingHeader::new(title)
-        .default_open(false)
+pub fn drill_ui(ui: &mut Ui, packet: &Frame, selection: &mut ByteSelection) {
+    let tree = packet.tree();
+    let mut clicked = None;
+    ScrollArea::vertical()
+        .auto_shrink([false, false])
         .show(ui, |ui| {
-            content(ui);
+            for (i, node) in tree.iter().enumerate() {
+                node_ui(ui, node, vec![i], selection, &mut clicked);
+            }
         });
+    selection.reveal_node = false;
+
+    if let Some(path) = clicked {
+        selection.select(&tree, path);
+        selection.reveal_bytes = true;
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+fn node_ui(
+    ui: &mut Ui,
+    node: &TreeNode,
+    path: Vec<usize>,
+    selection: &ByteSelection,
+    clicked: &mut Option<Vec<usize>>,
+) {
+    let selected = selection.path == path;
+
+    let response = match node.children.is_empty() {
+        true => ui.selectable_label(selected, &node.label),
+        false => {
+            let id = ui.make_persistent_id(&path);
+            let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+            // opened on the way down to a node picked from the payload pane
+            let below = selection.path.len() > path.len() && selection.path.starts_with(&path);
+            if selection.reveal_node && below {
+                state.set_open(true);
+            }
+            let (_, header, _) = state
+                .show_header(ui, |ui| ui.selectable_label(selected, &node.label))
+                .body(|ui| {
+                    for (i, child) in node.children.iter().enumerate() {
+                        let path = { path.iter().copied() }.chain([i]).collect();
+                        node_ui(ui, child, path, selection, clicked);
+                    }
+                });
+            header.inner
+        }
+    };
+
+    if selected && selection.reveal_node {
+        response.scroll_to_me(Some(Align::Center));
+    }
+    if response.clicked() {
+        *clicked = Some(path);
+    }
+    if let Some(range) = &node.range {
+        response.on_hover_text(format!("{} bytes at {:#06x}", range.len(), range.start));
+    }
 }
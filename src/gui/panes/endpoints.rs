@@ -0,0 +1,162 @@
+use crate::analysis::conversations::Level;
+use crate::analysis::endpoints::{Endpoint, Endpoints};
+use crate::packets::frame::Frame;
+use egui_extras::{Column, TableBuilder};
+use std::cmp::Ordering;
+
+const TITLES: [&str; 10] = [
+    "address",
+    "packets",
+    "bytes",
+    "packets sent",
+    "bytes sent",
+    "packets received",
+    "bytes received",
+    "first seen",
+    "last seen",
+    "top peers",
+];
+
+#[derive(Default, Clone)]
+pub struct EndpointsPane {
+    level: Level,
+    table: Endpoints,
+    search: String,
+    // the column sorted on, first seen order when there isn't one
+    sort_by: Option<usize>,
+    descending: bool,
+}
+
+impl EndpointsPane {
+    // clicking an endpoint puts a filter for it in `display_filter`
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        data: &[Frame],
+        generation: u32,
+        display_filter: &mut Option<String>,
+    ) {
+        self.table.update(data, generation);
+
+        ui.horizontal(|ui| {
+            for level in Level::ALL {
+                let count = self.table.at_level(level).count();
+                ui.selectable_value(&mut self.level, level, format!("{level} ({count})"));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("address or port")
+                    .desired_width(200.0),
+            );
+        });
+
+        let search = self.search.trim().to_lowercase();
+        let mut rows = { self.table.at_level(self.level) }
+            .filter(|endpoint| {
+                endpoint
+                    .address
+                    .to_string()
+                    .to_lowercase()
+                    .contains(&search)
+            })
+            .collect::<Vec<&Endpoint>>();
+        if let Some(column) = self.sort_by {
+            rows.sort_by(|a, b| match self.descending {
+                true => compare(b, a, column),
+                false => compare(a, b, column),
+            });
+        }
+
+        let mut clicked_column = None;
+        TableBuilder::new(ui)
+            .striped(true)
+            .auto_shrink([false, true])
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .columns(Column::auto(), TITLES.len())
+            .min_scrolled_height(0.0)
+            .resizable(true)
+            .header(20.0, |mut header| {
+                for (i, title) in TITLES.into_iter().enumerate() {
+                    header.col(|ui| {
+                        let title = match (self.sort_by == Some(i), self.descending) {
+                            (true, true) => format!("{title} ⏷"),
+                            (true, false) => format!("{title} ⏶"),
+                            (false, _) => title.to_owned(),
+                        };
+                        if ui.button(egui::RichText::new(title).strong()).clicked() {
+                            clicked_column = Some(i);
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(18.0, rows.len(), |index, mut row| {
+                    let endpoint = rows[index];
+                    let top_peers = endpoint.top_peers(3);
+                    let cells = [
+                        endpoint.address.to_string(),
+                        endpoint.packets().to_string(),
+                        endpoint.bytes().to_string(),
+                        endpoint.packets_sent.to_string(),
+                        endpoint.bytes_sent.to_string(),
+                        endpoint.packets_received.to_string(),
+                        endpoint.bytes_received.to_string(),
+                        endpoint
+                            .first_seen
+                            .format("%Y-%m-%d %H:%M:%S%.6f")
+                            .to_string(),
+                        endpoint
+                            .last_seen
+                            .format("%Y-%m-%d %H:%M:%S%.6f")
+                            .to_string(),
+                        { top_peers.iter() }
+                            .map(|(peer, bytes)| format!("{peer} ({bytes} bytes)"))
+                            .collect::<Vec<String>>()
+                            .join(", "),
+                    ];
+                    for text in cells {
+                        row.col(|ui| {
+                            let response =
+                                ui.button(text).on_hover_text("show in the packet table");
+                            if response.clicked() {
+                                *display_filter = Some(endpoint.address.filter(endpoint.level));
+                            }
+                        });
+                    }
+                });
+            });
+
+        // biggest first on the first click, the other way round on the next
+        if let Some(column) = clicked_column {
+            self.descending = self.sort_by != Some(column) || !self.descending;
+            self.sort_by = Some(column);
+        }
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+// in the order of `TITLES`
+fn compare(a: &Endpoint, b: &Endpoint, column: usize) -> Ordering {
+    match column {
+        0 => a.address.cmp(&b.address),
+        1 => a.packets().cmp(&b.packets()),
+        2 => a.bytes().cmp(&b.bytes()),
+        3 => a.packets_sent.cmp(&b.packets_sent),
+        4 => a.bytes_sent.cmp(&b.bytes_sent),
+        5 => a.packets_received.cmp(&b.packets_received),
+        6 => a.bytes_received.cmp(&b.bytes_received),
+        7 => a.first_seen.cmp(&b.first_seen),
+        8 => a.last_seen.cmp(&b.last_seen),
+        _ => a.peers.len().cmp(&b.peers.len()),
+    }
+}
@@ -0,0 +1,244 @@
+use crate::analysis::tcp_stream::{Direction, StreamChunk, TcpStream};
+use egui::{Color32, ComboBox, FontFamily::Monospace, Label, RichText, ScrollArea, Sense};
+use std::fmt::Write;
+
+const CLIENT_COLOR: Color32 = Color32::from_rgb(230, 110, 110);
+const SERVER_COLOR: Color32 = Color32::from_rgb(110, 150, 230);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StreamView {
+    Ascii,
+    Hex,
+    Raw,
+}
+
+#[derive(Clone)]
+pub struct FollowStreamPane {
+    pub stream: u32,
+    view: StreamView,
+    // None shows both directions
+    direction: Option<Direction>,
+    save_error: Option<String>,
+    closed: bool,
+}
+
+impl FollowStreamPane {
+    pub fn new(stream: u32) -> Self {
+        FollowStreamPane {
+            stream,
+            view: StreamView::Ascii,
+            direction: None,
+            save_error: None,
+            closed: false,
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        streams: &[TcpStream],
+        selected_packet: &mut Option<i32>,
+    ) {
+        let Some(stream) = streams.get(self.stream as usize) else {
+            ui.label(format!("tcp stream {} is not in this capture", self.stream));
+            if ui.button("Close").clicked() {
+                self.closed = true;
+            }
+            return;
+        };
+        let (client, server) = (&stream.client, &stream.server);
+        let client_bytes = direction_bytes(stream, Direction::ClientToServer).len();
+        let server_bytes = direction_bytes(stream, Direction::ServerToClient).len();
+
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!(
+                    "{}:{} → {}:{} ({client_bytes} bytes)",
+                    client.0, client.1, server.0, server.1
+                ))
+                .color(CLIENT_COLOR),
+            );
+            ui.label(
+                RichText::new(format!(
+                    "{}:{} → {}:{} ({server_bytes} bytes)",
+                    server.0, server.1, client.0, client.1
+                ))
+                .color(SERVER_COLOR),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ComboBox::from_id_source(("follow stream direction", self.stream))
+                .selected_text(direction_name(self.direction))
+                .show_ui(ui, |ui| {
+                    for direction in [
+                        None,
+                        Some(Direction::ClientToServer),
+                        Some(Direction::ServerToClient),
+                    ] {
+                        ui.selectable_value(
+                            &mut self.direction,
+                            direction,
+                            direction_name(direction),
+                        );
+                    }
+                });
+
+            ui.radio_value(&mut self.view, StreamView::Ascii, "ASCII");
+            ui.radio_value(&mut self.view, StreamView::Hex, "Hex dump");
+            ui.radio_value(&mut self.view, StreamView::Raw, "Raw");
+
+            for direction in [Direction::ClientToServer, Direction::ServerToClient] {
+                if ui
+                    .button(format!("Save {}", direction_name(Some(direction))))
+                    .clicked()
+                {
+                    self.save(stream, direction);
+                }
+            }
+            if ui.button("Close").clicked() {
+                self.closed = true;
+            }
+        });
+
+        if let Some(ref error) = self.save_error {
+            ui.colored_label(Color32::RED, error);
+        }
+        ui.separator();
+
+        ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                // running offset into each direction's data, for the hex dump
+                let mut offsets = [0usize; 2];
+                for run in runs(&stream.chunks) {
+                    let direction = run[0].direction;
+                    if self.direction.is_some_and(|shown| shown != direction) {
+                        continue;
+                    }
+                    let data = { run.iter() }
+                        .flat_map(|chunk| chunk.data.iter().copied())
+                        .collect::<Vec<u8>>();
+                    let offset = &mut offsets[direction as usize];
+
+                    let text = match self.view {
+                        StreamView::Ascii => ascii(&data),
+                        StreamView::Hex => hex_dump(&data, *offset, direction),
+                        StreamView::Raw => raw(&data),
+                    };
+                    *offset += data.len();
+
+                    let color = match direction {
+                        Direction::ClientToServer => CLIENT_COLOR,
+                        Direction::ServerToClient => SERVER_COLOR,
+                    };
+                    let label = Label::new(RichText::new(text).family(Monospace).color(color))
+                        .sense(Sense::click());
+                    if ui.add(label).on_hover_text(frames_text(run)).clicked() {
+                        *selected_packet = Some(run[0].frame);
+                    }
+                }
+            });
+    }
+
+    fn save(&mut self, stream: &TcpStream, direction: Direction) {
+        let side = match direction {
+            Direction::ClientToServer => "client",
+            Direction::ServerToClient => "server",
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(format!("tcp-stream-{}-{side}.bin", self.stream))
+            .save_file()
+        else {
+            return;
+        };
+        self.save_error = std::fs::write(&path, direction_bytes(stream, direction))
+            .err()
+            .map(|e| format!("could not save {}: {e}", path.display()));
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+fn direction_name(direction: Option<Direction>) -> &'static str {
+    match direction {
+        None => "Entire conversation",
+        Some(Direction::ClientToServer) => "client → server",
+        Some(Direction::ServerToClient) => "server → client",
+    }
+}
+
+fn direction_bytes(stream: &TcpStream, direction: Direction) -> Vec<u8> {
+    { stream.chunks.iter() }
+        .filter(|chunk| chunk.direction == direction)
+        .flat_map(|chunk| chunk.data.iter().copied())
+        .collect()
+}
+
+// consecutive chunks going the same way
+fn runs(chunks: &[StreamChunk]) -> impl Iterator<Item = &[StreamChunk]> {
+    chunks.chunk_by(|a, b| a.direction == b.direction)
+}
+
+fn frames_text(run: &[StreamChunk]) -> String {
+    let frames = { run.iter() }
+        .map(|chunk| chunk.frame.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("frames {frames}")
+}
+
+fn ascii(data: &[u8]) -> String {
+    { data.iter() }
+        .filter(|&&b| b != b'\r')
+        .map(|&b| match b {
+            b'\n' | b'\t' | b' ' => b as char,
+            b if b.is_ascii_graphic() => b as char,
+            _ => '.',
+        })
+        .collect()
+}
+
+fn hex_dump(data: &[u8], offset: usize, direction: Direction) -> String {
+    // server data is indented so the two sides can be told apart without color
+    let indent = match direction {
+        Direction::ClientToServer => "",
+        Direction::ServerToClient => "    ",
+    };
+    let mut dump = String::new();
+    for (i, line) in data.chunks(16).enumerate() {
+        if i > 0 {
+            dump.push('\n');
+        }
+        write!(dump, "{indent}{:08x}  ", offset + i * 16).unwrap();
+        for b in line {
+            write!(dump, "{b:02x} ").unwrap();
+        }
+        for _ in line.len()..16 {
+            dump.push_str("   ");
+        }
+        let string = { line.iter() }
+            .map(|&b| if b.is_ascii_graphic() { b as char } else { '.' })
+            .collect::<String>();
+        write!(dump, " {string}").unwrap();
+    }
+    dump
+}
+
+fn raw(data: &[u8]) -> String {
+    let mut raw = String::with_capacity(data.len() * 2);
+    for b in data {
+        write!(raw, "{b:02x}").unwrap();
+    }
+    raw
+}
@@ -1,10 +1,9 @@
-use crate::packets::data_link::ethernet::EthernetFrame;
-use chrono::{DateTime, Utc};
+use crate::packets::frame::Frame;
 use egui::Ui;
 use egui_plot::{Legend, Line, Plot, PlotPoints};
 use std::collections::BTreeMap;
 
-pub fn graph_ui(ui: &mut Ui, captured_packets: &[EthernetFrame]) {
+pub fn graph_ui(ui: &mut Ui, captured_packets: &[Frame]) {
     let plot = Plot::new("lines")
         .legend(Legend::default())
         .include_y(0.0)
@@ -16,9 +15,8 @@ pub fn graph_ui(ui: &mut Ui, captured_packets: &[EthernetFrame]) {
 
     let mut distribution = BTreeMap::<i64, [f64; 2]>::new();
     for p in captured_packets.iter().rev() {
-        let ts = p.timestamp.parse::<DateTime<Utc>>().unwrap();
-
-        let ts = (ts.timestamp() as f64) + (ts.timestamp_subsec_micros() as f64) / 1e6;
+        let ts = (p.timestamp.timestamp() as f64)
+            + (p.timestamp.timestamp_subsec_micros() as f64) / 1e6;
         // Multiply each second by a number to get that many entries per second.
         distribution.entry((ts * 10.0) as i64).or_insert([ts, 0.0])[1] += 1.0;
         if distribution.len() >= (10 * 10 + 2) {
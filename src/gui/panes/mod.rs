@@ -1,4 +1,8 @@
+pub mod arp_table;
+pub mod conversations;
 pub mod drill_down;
+pub mod endpoints;
+pub mod follow_stream;
 pub mod graph;
 pub mod packet_table;
 pub mod payload;
@@ -1,10 +1,15 @@
+use crate::export::csv::write_csv;
+use crate::filter::DisplayFilter;
 use crate::packets::{
-    data_link::ethernet::EthernetFrame,
-    packet_traits::{Describable, Layer},
-    shared_objs::LayerData,
+    application::registry::{self, TransportProtocol},
+    frame::Frame,
+    packet_traits::Describable,
+    shared_objs::{Description, Transport},
 };
 
 use egui_extras::{Column, TableBuilder};
+use std::fs::File;
+use std::io::BufWriter;
 
 #[derive(Default, Clone)]
 pub struct PacketTable {
@@ -12,15 +17,51 @@ pub struct PacketTable {
     resizable: bool,
     //scroll_to_row_slider: usize,
     scroll_to_row: Option<usize>,
+    filter_text: String,
+    filter: Option<DisplayFilter>,
+    filter_error: Option<String>,
+    // indexes into the captured packets that pass the filter
+    shown: Vec<usize>,
+    // how many packets the filter has been run over
+    checked: usize,
+    // the capture `shown` and `checked` are about, see `Sniffer::generation`
+    generation: u32,
+    // how much of `Sniffer::changed_packets` has been checked again
+    changes_checked: usize,
+    export_error: Option<String>,
 }
 
 impl PacketTable {
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
-        data: &[EthernetFrame],
+        data: &[Frame],
+        generation: u32,
+        changed: &[i32],
         selected_packet: &mut Option<i32>,
+        follow_stream: &mut Option<u32>,
+        redissect: &mut bool,
     ) {
+        self.filter_bar(ui);
+        self.update_shown(data, generation, changed);
+        let rows = match self.filter {
+            Some(_) => self.shown.len(),
+            None => data.len(),
+        };
+        ui.horizontal(|ui| {
+            ui.label(format!("Displayed: {rows} of {}", data.len()));
+            if ui
+                .add_enabled(rows > 0, egui::Button::new("Export as CSV"))
+                .clicked()
+            {
+                self.export_csv(data);
+            }
+        });
+        if let Some(error) = &self.export_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
         let mut table = TableBuilder::new(ui)
             .striped(self.striped)
             .resizable(self.resizable)
@@ -32,6 +73,7 @@ impl PacketTable {
             .column(Column::auto())
             .column(Column::auto())
             .column(Column::auto())
+            .column(Column::auto())
             .min_scrolled_height(0.0)
             .resizable(true);
 
@@ -41,52 +83,216 @@ impl PacketTable {
 
         table
             .header(20.0, |mut header| {
-                header.col(|ui| {
-                    ui.strong("id");
-                });
-                header.col(|ui| {
-                    ui.strong("timestamp");
-                });
-                header.col(|ui| {
-                    ui.strong("source");
-                });
-                header.col(|ui| {
-                    ui.strong("destination");
-                });
-                header.col(|ui| {
-                    ui.strong("info");
-                });
+                for name in Description::COLUMNS {
+                    header.col(|ui| {
+                        ui.strong(name);
+                    });
+                }
             })
             .body(|body| {
-                body.rows(18.0, data.len(), |index, mut row| {
-                    let packet = &data[index];
-                    let description = packet.get_description();
-                    let info = match description.info_layer {
-                        LayerData::Layer(layer) => layer.info(),
-                        LayerData::Application(layer) => layer.info(),
-                        LayerData::Data(_) => {
-                            panic!(
-                                "shouldnt happen, in packet table \n packet summary:{}",
-                                packet.get_summary()
-                            )
-                        }
+                body.rows(18.0, rows, |index, mut row| {
+                    let packet = match self.filter {
+                        Some(_) => &data[self.shown[index]],
+                        None => &data[index],
                     };
-                    [
-                        description.id.to_string().as_str(),
-                        description.timestamp,
-                        description.src_dest_layer.source().as_ref(),
-                        description.src_dest_layer.destination().as_ref(),
-                        info.as_str(),
-                    ]
-                    .into_iter()
-                    .for_each(|text| {
+                    let description = packet.get_description();
+                    description.columns().into_iter().for_each(|text| {
                         row.col(|ui| {
-                            if ui.button(text).clicked() {
+                            let response = ui.button(text);
+                            if response.clicked() {
                                 *selected_packet = Some(description.id);
                             }
+                            let stream = tcp_stream(packet);
+                            let ports = transport_ports(packet);
+                            if stream.is_some() || ports.is_some() {
+                                response.context_menu(|ui| {
+                                    if let Some(stream) = stream {
+                                        if ui.button("Follow TCP stream").clicked() {
+                                            *follow_stream = Some(stream);
+                                            ui.close_menu();
+                                        }
+                                    }
+                                    if let Some((transport, ports)) = ports {
+                                        decode_as_menu(ui, transport, ports, redissect);
+                                    }
+                                });
+                            }
                         });
                     });
                 });
             });
+
+        // everything gets dissected again, so filtered again too
+        if *redissect {
+            self.checked = 0;
+        }
+    }
+
+    // filters the table as if `text` had been typed in and applied
+    pub fn set_filter(&mut self, text: &str) {
+        self.filter_text = text.to_owned();
+        self.apply_filter();
+    }
+
+    fn filter_bar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.filter_text)
+                    .hint_text("e.g. ip.src == 10.0.0.1 && tcp.port in {80 443}")
+                    .desired_width(400.0),
+            );
+            let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("Apply").clicked() || entered {
+                self.apply_filter();
+            }
+            if ui.button("Clear").clicked() {
+                self.filter_text.clear();
+                self.apply_filter();
+            }
+            // checked as it's typed, the filter in use only changes on apply
+            if response.changed() {
+                self.filter_error = match self.filter_text.trim().is_empty() {
+                    true => None,
+                    false => DisplayFilter::parse(&self.filter_text)
+                        .err()
+                        .map(|e| e.to_string()),
+                };
+            }
+        });
+        if let Some(error) = &self.filter_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        if self.filter_text.trim().is_empty() {
+            self.filter = None;
+            self.filter_error = None;
+        } else {
+            match DisplayFilter::parse(&self.filter_text) {
+                Ok(filter) => {
+                    self.filter = Some(filter);
+                    self.filter_error = None;
+                }
+                Err(e) => {
+                    self.filter_error = Some(e.to_string());
+                    return;
+                }
+            }
+        }
+        self.shown.clear();
+        self.checked = 0;
+    }
+
+    // the rows as they're shown, so the export matches the table
+    fn export_csv(&mut self, data: &[Frame]) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("packets.csv")
+            .save_file()
+        else {
+            return;
+        };
+        let shown = match self.filter {
+            Some(_) => { self.shown.iter() }.map(|&i| &data[i]).collect(),
+            None => data.iter().collect::<Vec<&Frame>>(),
+        };
+        let result = File::create(path).and_then(|file| write_csv(BufWriter::new(file), shown));
+        self.export_error = result.err().map(|e| format!("could not export: {e}"));
+    }
+
+    // only the packets captured since the last frame need checking, along with
+    // the ones analysis changed since, unless a new capture replaced them all
+    fn update_shown(&mut self, data: &[Frame], generation: u32, changed: &[i32]) {
+        let Some(filter) = &self.filter else {
+            return;
+        };
+        if self.generation != generation || self.checked == 0 {
+            self.shown.clear();
+            self.checked = 0;
+            self.generation = generation;
+            self.changes_checked = changed.len();
+        }
+
+        for &id in changed.get(self.changes_checked..).unwrap_or_default() {
+            let index = id as usize;
+            let Some(packet) = data.get(index).filter(|_| index < self.checked) else {
+                continue;
+            };
+            match (self.shown.binary_search(&index), filter.matches(packet)) {
+                (Err(position), true) => self.shown.insert(position, index),
+                (Ok(position), false) => {
+                    self.shown.remove(position);
+                }
+                _ => {}
+            }
+        }
+        self.changes_checked = changed.len();
+
+        let new = { data[self.checked..].iter().enumerate() }
+            .filter(|(_, packet)| filter.matches(packet))
+            .map(|(i, _)| self.checked + i);
+        self.shown.extend(new);
+        self.checked = data.len();
+    }
+}
+
+fn transport_ports(packet: &Frame) -> Option<(TransportProtocol, [u16; 2])> {
+    match packet.payload.network()?.transport()? {
+        Transport::TCP(tcp) => Some((
+            TransportProtocol::Tcp,
+            [tcp.header.source_port, tcp.header.destination_port],
+        )),
+        Transport::UDP(udp) => Some((
+            TransportProtocol::Udp,
+            [udp.header.source_port, udp.header.destination_port],
+        )),
+        _ => None,
+    }
+}
+
+// a submenu per port, picking a dissector for it or going back to the default
+fn decode_as_menu(
+    ui: &mut egui::Ui,
+    transport: TransportProtocol,
+    ports: [u16; 2],
+    redissect: &mut bool,
+) {
+    ui.menu_button("Decode as", |ui| {
+        let rules = registry::decode_as_rules();
+        let names = registry::dissector_names();
+        let mut ports = ports.to_vec();
+        ports.dedup();
+        for port in ports {
+            let current = { rules.iter() }
+                .find(|(t, p, _)| *t == transport && *p == port)
+                .map(|(_, _, name)| name.as_str());
+            ui.menu_button(format!("{transport} port {port}"), |ui| {
+                if ui.selectable_label(current.is_none(), "Default").clicked() {
+                    registry::clear_decode_as(transport, port);
+                    *redissect = true;
+                    ui.close_menu();
+                }
+                for name in &names {
+                    if ui
+                        .selectable_label(current == Some(name.as_str()), name)
+                        .clicked()
+                    {
+                        // the name comes from the registry, it can't be unknown
+                        let _ = registry::decode_as(transport, port, name);
+                        *redissect = true;
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+    });
+}
+
+fn tcp_stream(packet: &Frame) -> Option<u32> {
+    match packet.payload.network()?.transport()? {
+        Transport::TCP(tcp) => tcp.stream,
+        _ => None,
     }
 }
@@ -1,28 +1,80 @@
-use crate::packets::data_link::ethernet::EthernetFrame;
+use crate::gui::panes::drill_down::ByteSelection;
+use crate::packets::frame::Frame;
 use crate::packets::packet_traits::Layer;
 use crate::packets::shared_objs::LayerData;
-use egui::{FontFamily::Monospace, RichText, Ui};
+use crate::packets::tree::TreeNode;
+use egui::{FontFamily::Monospace, Label, RichText, Sense, Ui};
 use egui_extras::{Column, TableBody, TableBuilder};
-use std::fmt::Write;
-pub fn payload_ui(ui: &mut Ui, packet: &EthernetFrame) {
-    let mut layer_data = packet.get_next();
-    let payload = 'payload: loop {
-        match layer_data {
-            LayerData::Layer(layer) => {
-                layer_data = layer.get_next();
-            }
-            LayerData::Application(app_layer) => break 'payload LayerData::Application(app_layer),
-            LayerData::Data(payload) => {
-                break 'payload LayerData::Data(payload);
-            }
+use std::ops::Range;
+
+#[derive(Clone, Default)]
+pub struct PayloadPane {
+    // the innermost payload instead of the frame, e.g. a reassembled message
+    application: bool,
+}
+
+impl PayloadPane {
+    pub fn render(&mut self, ui: &mut Ui, packet: &Frame, selection: &mut ByteSelection) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.application, false, "Frame");
+            ui.selectable_value(&mut self.application, true, "Application data");
+        });
+
+        if self.application {
+            let mut layer_data = packet.get_next();
+            let payload = 'payload: loop {
+                match layer_data {
+                    LayerData::Layer(layer) => {
+                        layer_data = layer.get_next();
+                    }
+                    LayerData::Application(app_layer) => break 'payload app_layer.payload(),
+                    LayerData::Data(payload) => break 'payload payload.to_vec(),
+                }
+            };
+            bytes_table(ui, None, |body| {
+                display_payload(body, &payload, None);
+            });
+            return;
+        }
+
+        let scroll_to = { selection.range.as_ref() }
+            .filter(|_| selection.reveal_bytes)
+            .map(|range| range.start / 16);
+        selection.reveal_bytes = false;
+
+        let mut clicked = None;
+        bytes_table(ui, scroll_to, |body| {
+            clicked = display_payload(body, &packet.data, selection.range.as_ref());
+        });
+
+        // picking a byte picks the innermost field it belongs to
+        if let Some(offset) = clicked {
+            let tree = packet.tree();
+            let path = TreeNode::path_at(&tree, offset).unwrap_or_default();
+            selection.select(&tree, path);
+            selection.reveal_node = true;
         }
-    };
+    }
+}
+
+/*
 
-    TableBuilder::new(ui)
+
+Privates
+
+
+*/
+
+fn bytes_table(ui: &mut Ui, scroll_to: Option<usize>, body: impl FnOnce(TableBody)) {
+    let mut table = TableBuilder::new(ui)
         .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
         .column(Column::auto())
         .column(Column::auto())
-        .column(Column::auto())
+        .column(Column::auto());
+    if let Some(row) = scroll_to {
+        table = table.scroll_to_row(row, Some(egui::Align::Center));
+    }
+    table
         .header(20.0, |mut header| {
             header.col(|ui| {
                 ui.strong("Address:");
@@ -34,27 +86,22 @@ pub fn payload_ui(ui: &mut Ui, packet: &EthernetFrame) {
                 ui.strong("String Data:");
             });
         })
-        .body(|body| match payload {
-            LayerData::Layer(layer) => {
-                panic!("layer data in payload.rs\n protocol: {}", layer.protocol())
-            }
-            LayerData::Application(app_layer) => display_payload(body, &app_layer.payload()),
-            LayerData::Data(payload) => display_payload(body, payload),
-        });
+        .body(body);
 }
 
-/*
-
-
-Privates
-
-
-*/
-
-fn display_payload(body: TableBody, payload: &[u8]) {
+// one row per 16 bytes, the `highlight`ed ones marked. Returns the offset of a
+// byte that was clicked.
+fn display_payload(
+    body: TableBody,
+    payload: &[u8],
+    highlight: Option<&Range<usize>>,
+) -> Option<usize> {
+    let mut clicked = None;
     let chunks = payload.chunks(16);
     body.rows(18.0, chunks.clone().count(), |index, mut row| {
         let chunk = chunks.clone().nth(index).unwrap();
+        let start = index * 16;
+        let highlighted = |offset: usize| highlight.is_some_and(|range| range.contains(&offset));
 
         row.col(|ui| {
             let address = format!("{index:07x}0:");
@@ -62,29 +109,45 @@ fn display_payload(body: TableBody, payload: &[u8]) {
         });
 
         row.col(|ui| {
-            let mut hex_string = String::new();
-            let mut crumbs = chunk.chunks(2);
-
-            // Write the first "crumb" separately,
-            for b in crumbs.next().unwrap().iter() {
-                write!(hex_string, "{b:02x}").unwrap();
-            }
-            // then write the following "crumbs" with a leading space
-            for crumb in crumbs {
-                write!(hex_string, " ").unwrap();
-                for b in crumb {
-                    write!(hex_string, "{b:02x}").unwrap();
+            ui.spacing_mut().item_spacing.x = 0.0;
+            for (i, b) in chunk.iter().enumerate() {
+                let offset = start + i;
+                // a space after every "crumb" of two bytes, marked when the
+                // bytes either side of it are
+                if i > 0 && i.is_multiple_of(2) {
+                    let joined = highlighted(offset - 1) && highlighted(offset);
+                    ui.label(byte_text(" ".to_owned(), joined, ui));
+                }
+                let text = byte_text(format!("{b:02x}"), highlighted(offset), ui);
+                if ui.add(Label::new(text).sense(Sense::click())).clicked() {
+                    clicked = Some(offset);
                 }
             }
-
-            ui.label(RichText::new(hex_string).family(Monospace));
         });
 
         row.col(|ui| {
-            let string = { chunk.iter() }
-                .map(|&b| if b.is_ascii_graphic() { b as char } else { '.' })
-                .collect::<String>();
-            ui.label(RichText::new(string).family(Monospace));
+            ui.spacing_mut().item_spacing.x = 0.0;
+            for (i, &b) in chunk.iter().enumerate() {
+                let offset = start + i;
+                let c = if b.is_ascii_graphic() { b as char } else { '.' };
+                let text = byte_text(c.to_string(), highlighted(offset), ui);
+                if ui.add(Label::new(text).sense(Sense::click())).clicked() {
+                    clicked = Some(offset);
+                }
+            }
         });
     });
+    clicked
+}
+
+fn byte_text(text: String, highlighted: bool, ui: &Ui) -> RichText {
+    let text = RichText::new(text).family(Monospace);
+    match highlighted {
+        true => {
+            let selection = &ui.visuals().selection;
+            text.background_color(selection.bg_fill)
+                .color(selection.stroke.color)
+        }
+        false => text,
+    }
 }
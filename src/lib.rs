@@ -1,3 +1,6 @@
+pub mod analysis;
+pub mod export;
+pub mod filter;
 pub mod gui;
 pub mod packets;
 pub mod sniffer;
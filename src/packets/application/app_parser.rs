@@ -1,13 +1,41 @@
+use crate::packets::application::http::HttpRequestType;
 use crate::packets::shared_objs::Application;
 
-use super::dns::DnsMessage;
+use super::registry;
 
 pub struct AppParser;
 
-pub fn parse_app_layer(data: &[u8]) -> Application {
-    if let Ok(dns_message) = DnsMessage::new(data) {
-        Application::Dns(dns_message)
-    } else {
-        Application::Other(data.to_vec().into_boxed_slice())
-    }
+// result of trying to pull one message off the front of a reassembled stream
+pub enum StreamParse {
+    // a whole message and how many bytes of the stream it used, at least one
+    // and no more than it was given. Anything else is taken as `Unknown`.
+    Message(Application, usize),
+    NeedMore,
+    // not something we can decode
+    Unknown,
+}
+
+// what the reassembler knows about a stream besides the data itself
+#[derive(Debug, Clone, Default)]
+pub struct StreamContext {
+    // the sender closed its side and everything it sent has been delivered,
+    // a message running to the end of the connection is complete now
+    pub closed: bool,
+    // the method of the oldest http request sent the other way that hasn't
+    // been answered, the response to a HEAD has no body whatever its headers say
+    pub http_request: Option<HttpRequestType>,
+}
+
+// which dissector gets the data is up to the registry, see `registry::AppDissector`
+pub fn parse_app_layer(source_port: u16, destination_port: u16, data: &[u8]) -> Application {
+    registry::dissect_datagram(source_port, destination_port, data)
+}
+
+pub fn parse_app_stream(
+    source_port: u16,
+    destination_port: u16,
+    data: &[u8],
+    context: &StreamContext,
+) -> StreamParse {
+    registry::dissect_stream(source_port, destination_port, data, context)
 }
@@ -1,9 +1,16 @@
-use crate::packets::{packet_traits::AppLayer, shared_objs::Protocol};
+use crate::packets::application::app_parser::StreamParse;
+use crate::packets::{
+    fields::Field,
+    packet_traits::AppLayer,
+    shared_objs::{Application, Protocol},
+    tree::TreeNode,
+};
 use core::fmt;
+use std::net::IpAddr;
 use trust_dns_proto::{
     error::ProtoError,
-    op::{op_code, Header, Message, MessageParts, MessageType, Query},
-    rr::Record,
+    op::{op_code, Edns, Header, Message, MessageParts, MessageType, Query, ResponseCode},
+    rr::{Name, RData, Record},
     serialize::binary::BinDecodable,
 };
 
@@ -12,6 +19,12 @@ pub struct DnsMessage {
     pub header: Header,
     pub questions: Vec<Query>,
     pub answers: Vec<Record>,
+    pub authority: Vec<Record>,
+    pub additionals: Vec<Record>,
+    // the OPT pseudo record, trust-dns takes it out of the additional section
+    pub edns: Option<Edns>,
+    // dns over tcp, the message came after a two byte length
+    pub length_prefixed: bool,
 }
 
 impl DnsMessage {
@@ -23,6 +36,10 @@ impl DnsMessage {
                     header: dns_message.header,
                     questions: dns_message.queries,
                     answers: dns_message.answers,
+                    authority: dns_message.name_servers,
+                    additionals: dns_message.additionals,
+                    edns: dns_message.edns,
+                    length_prefixed: false,
                 })
             }
             Err(e) => Err(e),
@@ -39,6 +56,27 @@ impl DnsMessage {
     }
 }
 
+// dns over tcp, every message has a two byte length in front of it
+pub fn parse_stream(data: &[u8]) -> StreamParse {
+    let [length_hi, length_lo, message @ ..] = data else {
+        return StreamParse::NeedMore;
+    };
+    let length = u16::from_be_bytes([*length_hi, *length_lo]) as usize;
+    let Some(message) = message.get(..length) else {
+        return StreamParse::NeedMore;
+    };
+    match DnsMessage::new(message) {
+        Ok(dns_message) => {
+            let dns_message = DnsMessage {
+                length_prefixed: true,
+                ..dns_message
+            };
+            StreamParse::Message(Application::Dns(dns_message), length + 2)
+        }
+        Err(_) => StreamParse::Unknown,
+    }
+}
+
 fn parse_dns_message(data: &[u8]) -> Result<Message, trust_dns_proto::error::ProtoError> {
     let dns_message = Message::from_bytes(data)?;
     Ok(dns_message)
@@ -46,20 +84,276 @@ fn parse_dns_message(data: &[u8]) -> Result<Message, trust_dns_proto::error::Pro
 
 impl AppLayer for DnsMessage {
     fn get_summary(&self) -> String {
-        format!("")
+        let header = &self.header;
+        let mut summary = format!(
+            "transaction id: 0x{:04x}
+type: {}
+opcode: {}
+authoritative: {}
+truncated: {}
+recursion desired: {}
+recursion available: {}
+authentic data: {}
+checking disabled: {}
+rcode: {} ({})
+questions: {}
+answer rrs: {}
+authority rrs: {}
+additional rrs: {}",
+            header.id(),
+            DnsMessageType(header.message_type()),
+            DnsOpCode(header.op_code()),
+            header.authoritative(),
+            header.truncated(),
+            header.recursion_desired(),
+            header.recursion_available(),
+            header.authentic_data(),
+            header.checking_disabled(),
+            DnsResponseCode(header.response_code()),
+            header.response_code(),
+            header.query_count(),
+            header.answer_count(),
+            header.name_server_count(),
+            header.additional_count(),
+        );
+
+        if !self.questions.is_empty() {
+            summary.push_str("\nqueries:");
+            for question in &self.questions {
+                summary.push_str(&format!(
+                    "\n    {}: type {}, class {}",
+                    name(question.name()),
+                    question.query_type(),
+                    question.query_class()
+                ));
+            }
+        }
+        for (section, records) in [
+            ("answers", &self.answers),
+            ("authoritative nameservers", &self.authority),
+            ("additional records", &self.additionals),
+        ] {
+            if !records.is_empty() {
+                summary.push_str(&format!("\n{section}:"));
+                for record in records {
+                    summary.push_str(&format!("\n    {}", format_record(record)));
+                }
+            }
+        }
+        if let Some(ref edns) = self.edns {
+            if self.additionals.is_empty() {
+                summary.push_str("\nadditional records:");
+            }
+            summary.push_str(&format!(
+                "\n    <Root>: type OPT, udp payload size {}, edns version {}{}",
+                edns.max_payload(),
+                edns.version(),
+                if edns.dnssec_ok() { ", do" } else { "" },
+            ));
+        }
+        summary
     }
 
     fn protocol(&self) -> Protocol {
         Protocol::DNS
     }
 
+    // "Standard query 0x1a2b A example.com" or
+    // "Standard query response 0x1a2b NXDOMAIN A nope.example.com"
     fn info(&self) -> String {
-        format!("dns")
+        let header = &self.header;
+        let mut info = DnsOpCode(header.op_code()).to_string();
+        if header.message_type() == MessageType::Response {
+            info.push_str(" response");
+        }
+        info.push_str(&format!(" 0x{:04x}", header.id()));
+        if header.message_type() == MessageType::Response
+            && header.response_code() != ResponseCode::NoError
+        {
+            info.push_str(&format!(" {}", DnsResponseCode(header.response_code())));
+        }
+        for question in &self.questions {
+            info.push_str(&format!(
+                " {} {}",
+                question.query_type(),
+                name(question.name())
+            ));
+        }
+        for answer in &self.answers {
+            info.push_str(&format!(" {}", answer.record_type()));
+            if let Some(data) = answer.data() {
+                info.push_str(&format!(" {data}"));
+            }
+        }
+        info
     }
 
     fn payload(&self) -> Vec<u8> {
         vec![]
     }
+
+    fn fields(&self) -> Vec<Field> {
+        let header = &self.header;
+        let mut fields = vec![
+            Field::new("dns.id", header.id()),
+            Field::new(
+                "dns.flags.response",
+                header.message_type() == MessageType::Response,
+            ),
+            Field::new("dns.flags.opcode", u8::from(header.op_code())),
+            Field::new("dns.flags.authoritative", header.authoritative()),
+            Field::new("dns.flags.truncated", header.truncated()),
+            Field::new("dns.flags.recdesired", header.recursion_desired()),
+            Field::new("dns.flags.recavail", header.recursion_available()),
+            Field::new("dns.flags.rcode", u16::from(header.response_code())),
+            Field::new("dns.count.queries", header.query_count()),
+            Field::new("dns.count.answers", header.answer_count()),
+            Field::new("dns.count.auth_rr", header.name_server_count()),
+            Field::new("dns.count.add_rr", header.additional_count()),
+        ];
+        for question in &self.questions {
+            fields.push(Field::new("dns.qry.name", name(question.name())));
+            fields.push(Field::new("dns.qry.type", u16::from(question.query_type())));
+            fields.push(Field::new(
+                "dns.qry.class",
+                u16::from(question.query_class()),
+            ));
+        }
+        let records = { self.answers.iter() }
+            .chain(&self.authority)
+            .chain(&self.additionals);
+        for record in records {
+            fields.push(Field::new("dns.resp.name", name(record.name())));
+            fields.push(Field::new("dns.resp.type", u16::from(record.record_type())));
+            fields.push(Field::new("dns.resp.ttl", record.ttl()));
+            fields.extend(match record.data() {
+                Some(RData::A(a)) => Some(Field::new("dns.a", IpAddr::V4(a.0))),
+                Some(RData::AAAA(aaaa)) => Some(Field::new("dns.aaaa", IpAddr::V6(aaaa.0))),
+                Some(RData::CNAME(cname)) => Some(Field::new("dns.cname", name(&cname.0))),
+                Some(RData::NS(ns)) => Some(Field::new("dns.ns", name(&ns.0))),
+                Some(RData::PTR(ptr)) => Some(Field::new("dns.ptr.domain_name", name(&ptr.0))),
+                _ => None,
+            });
+        }
+        fields
+    }
+
+    // the header is laid out by byte, the sections only by what's in them
+    fn tree(&self) -> TreeNode {
+        let header = &self.header;
+        let mut children = vec![];
+        let start = match self.length_prefixed {
+            true => {
+                children.push(TreeNode::at("length prefix (dns over tcp)", 0, 2));
+                2
+            }
+            false => 0,
+        };
+        let flags = vec![
+            TreeNode::at(
+                format!("type: {}", DnsMessageType(header.message_type())),
+                start + 2,
+                1,
+            ),
+            TreeNode::at(
+                format!("opcode: {}", DnsOpCode(header.op_code())),
+                start + 2,
+                1,
+            ),
+            TreeNode::at(
+                format!("authoritative: {}", header.authoritative()),
+                start + 2,
+                1,
+            ),
+            TreeNode::at(format!("truncated: {}", header.truncated()), start + 2, 1),
+            TreeNode::at(
+                format!("recursion desired: {}", header.recursion_desired()),
+                start + 2,
+                1,
+            ),
+            TreeNode::at(
+                format!("recursion available: {}", header.recursion_available()),
+                start + 3,
+                1,
+            ),
+            TreeNode::at(
+                format!("authentic data: {}", header.authentic_data()),
+                start + 3,
+                1,
+            ),
+            TreeNode::at(
+                format!("checking disabled: {}", header.checking_disabled()),
+                start + 3,
+                1,
+            ),
+            TreeNode::at(
+                format!(
+                    "rcode: {} ({})",
+                    DnsResponseCode(header.response_code()),
+                    header.response_code()
+                ),
+                start + 3,
+                1,
+            ),
+        ];
+        children.extend([
+            TreeNode::at(format!("transaction id: 0x{:04x}", header.id()), start, 2),
+            TreeNode::at("flags", start + 2, 2).with_children(flags),
+            TreeNode::at(format!("questions: {}", header.query_count()), start + 4, 2),
+            TreeNode::at(
+                format!("answer rrs: {}", header.answer_count()),
+                start + 6,
+                2,
+            ),
+            TreeNode::at(
+                format!("authority rrs: {}", header.name_server_count()),
+                start + 8,
+                2,
+            ),
+            TreeNode::at(
+                format!("additional rrs: {}", header.additional_count()),
+                start + 10,
+                2,
+            ),
+        ]);
+
+        if !self.questions.is_empty() {
+            let queries = { self.questions.iter() }
+                .map(|question| {
+                    TreeNode::new(format!(
+                        "{}: type {}, class {}",
+                        name(question.name()),
+                        question.query_type(),
+                        question.query_class()
+                    ))
+                })
+                .collect();
+            children.push(TreeNode::new("queries").with_children(queries));
+        }
+        for (section, records) in [
+            ("answers", &self.answers),
+            ("authoritative nameservers", &self.authority),
+            ("additional records", &self.additionals),
+        ] {
+            if !records.is_empty() {
+                let records = { records.iter() }
+                    .map(|record| TreeNode::new(format_record(record)))
+                    .collect();
+                children.push(TreeNode::new(section).with_children(records));
+            }
+        }
+        if let Some(ref edns) = self.edns {
+            children.push(TreeNode::new(format!(
+                "edns: udp payload size {}, edns version {}{}",
+                edns.max_payload(),
+                edns.version(),
+                if edns.dnssec_ok() { ", do" } else { "" },
+            )));
+        }
+
+        let label = format!("{}, {}", self.protocol(), self.info());
+        TreeNode::at(label, 0, start + 12).with_children(children)
+    }
 }
 
 struct DnsMessageType(MessageType);
@@ -78,10 +372,62 @@ struct DnsOpCode(op_code::OpCode);
 impl fmt::Display for DnsOpCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.0 {
-            op_code::OpCode::Query => write!(f, "query"),
-            op_code::OpCode::Status => write!(f, "status"),
-            op_code::OpCode::Notify => write!(f, "notify"),
-            op_code::OpCode::Update => write!(f, "update"),
+            op_code::OpCode::Query => write!(f, "Standard query"),
+            op_code::OpCode::Status => write!(f, "Server status request"),
+            op_code::OpCode::Notify => write!(f, "Zone change notification"),
+            op_code::OpCode::Update => write!(f, "Dynamic update"),
+        }
+    }
+}
+
+// the rfc mnemonics, trust-dns only has the long descriptions
+struct DnsResponseCode(ResponseCode);
+
+impl fmt::Display for DnsResponseCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ResponseCode::NoError => write!(f, "NOERROR"),
+            ResponseCode::FormErr => write!(f, "FORMERR"),
+            ResponseCode::ServFail => write!(f, "SERVFAIL"),
+            ResponseCode::NXDomain => write!(f, "NXDOMAIN"),
+            ResponseCode::NotImp => write!(f, "NOTIMP"),
+            ResponseCode::Refused => write!(f, "REFUSED"),
+            ResponseCode::YXDomain => write!(f, "YXDOMAIN"),
+            ResponseCode::YXRRSet => write!(f, "YXRRSET"),
+            ResponseCode::NXRRSet => write!(f, "NXRRSET"),
+            ResponseCode::NotAuth => write!(f, "NOTAUTH"),
+            ResponseCode::NotZone => write!(f, "NOTZONE"),
+            code => write!(f, "RCODE{}", u16::from(code)),
         }
     }
 }
+
+/*
+
+
+Privates
+
+
+*/
+
+// names off the wire are fully qualified, the trailing dot is just noise here
+fn name(name: &Name) -> String {
+    match name.is_root() {
+        true => "<Root>".to_owned(),
+        false => name.to_string().trim_end_matches('.').to_owned(),
+    }
+}
+
+fn format_record(record: &Record) -> String {
+    let data = match record.data() {
+        Some(data) => data.to_string(),
+        None => "<no data>".to_owned(),
+    };
+    format!(
+        "{}: type {}, class {}, ttl {}, {data}",
+        name(record.name()),
+        record.record_type(),
+        record.dns_class(),
+        record.ttl()
+    )
+}
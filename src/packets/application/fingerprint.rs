@@ -0,0 +1,277 @@
+// JA3, JA3S and JA4 TLS fingerprints, computed from the hello messages.
+// https://github.com/salesforce/ja3 and https://github.com/FoxIO-LLC/ja4
+
+use crate::packets::application::tls::{
+    is_grease, ClientHello, ServerHello, EXTENSION_ALPN, EXTENSION_SERVER_NAME,
+};
+use std::fmt::Write;
+
+impl ClientHello {
+    // version,ciphers,extensions,groups,point formats
+    pub fn ja3(&self) -> String {
+        let extensions = { self.extensions.iter() }.map(|extension| extension.extension_type);
+        format!(
+            "{},{},{},{},{}",
+            self.version,
+            join_decimal(self.cipher_suites.iter().copied()),
+            join_decimal(extensions),
+            join_decimal(self.supported_groups.iter().copied()),
+            join_decimal(self.ec_point_formats.iter().copied().map(u16::from)),
+        )
+    }
+
+    pub fn ja3_hash(&self) -> String {
+        to_hex(&md5(self.ja3().as_bytes()))
+    }
+
+    pub fn ja4(&self) -> String {
+        let ciphers = { self.cipher_suites.iter().copied() }
+            .filter(|cipher| !is_grease(*cipher))
+            .collect::<Vec<u16>>();
+        let extensions = { self.extensions.iter() }
+            .map(|extension| extension.extension_type)
+            .filter(|extension| !is_grease(*extension))
+            .collect::<Vec<u16>>();
+
+        let version = match self.highest_version() {
+            0x0304 => "13",
+            0x0303 => "12",
+            0x0302 => "11",
+            0x0301 => "10",
+            0x0300 => "s3",
+            _ => "00",
+        };
+        let sni = match extensions.contains(&EXTENSION_SERVER_NAME) {
+            true => 'd',
+            false => 'i',
+        };
+        let alpn = match self.alpn.first().map(String::as_bytes) {
+            Some([first, .., last]) | Some([first @ last]) => ja4_alpn(*first, *last),
+            _ => "00".to_owned(),
+        };
+        let a = format!(
+            "t{version}{sni}{:02}{:02}{alpn}",
+            ciphers.len().min(99),
+            extensions.len().min(99)
+        );
+
+        let mut sorted_ciphers = ciphers;
+        sorted_ciphers.sort_unstable();
+        let b = ja4_hash(&join_hex(sorted_ciphers.into_iter()));
+
+        // sni and alpn are already part of a
+        let mut sorted_extensions = { extensions.into_iter() }
+            .filter(|extension| *extension != EXTENSION_SERVER_NAME && *extension != EXTENSION_ALPN)
+            .collect::<Vec<u16>>();
+        sorted_extensions.sort_unstable();
+        let mut c = join_hex(sorted_extensions.into_iter());
+        if !self.signature_algorithms.is_empty() {
+            c.push('_');
+            c.push_str(&join_hex(self.signature_algorithms.iter().copied()));
+        }
+        let c = ja4_hash(&c);
+
+        format!("{a}_{b}_{c}")
+    }
+}
+
+impl ServerHello {
+    // version,cipher,extensions
+    pub fn ja3s(&self) -> String {
+        let extensions = { self.extensions.iter() }.map(|extension| extension.extension_type);
+        format!(
+            "{},{},{}",
+            self.version,
+            self.cipher_suite,
+            join_decimal(extensions)
+        )
+    }
+
+    pub fn ja3s_hash(&self) -> String {
+        to_hex(&md5(self.ja3s().as_bytes()))
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+// GREASE values are left out, they change from connection to connection
+fn join_decimal(values: impl Iterator<Item = u16>) -> String {
+    { values.filter(|value| !is_grease(*value)) }
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+fn join_hex(values: impl Iterator<Item = u16>) -> String {
+    { values.map(|value| format!("{value:04x}")) }
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+// first and last character of the first protocol, or of its hex when those
+// aren't alphanumeric
+fn ja4_alpn(first: u8, last: u8) -> String {
+    if first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric() {
+        format!("{}{}", first as char, last as char)
+    } else {
+        let (first, last) = (format!("{first:02x}"), format!("{last:02x}"));
+        format!("{}{}", &first[..1], &last[1..])
+    }
+}
+
+// truncated sha256, all zeros when there was nothing to hash
+fn ja4_hash(list: &str) -> String {
+    if list.is_empty() {
+        return "000000000000".to_owned();
+    }
+    to_hex(&sha256(list.as_bytes()))[..12].to_owned()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        write!(hex, "{b:02x}").unwrap();
+    }
+    hex
+}
+
+// message padding shared by md5 and sha256, only the length's byte order differs
+fn pad(data: &[u8], big_endian: bool) -> Vec<u8> {
+    let bits = (data.len() as u64).wrapping_mul(8);
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend(match big_endian {
+        true => bits.to_be_bytes(),
+        false => bits.to_le_bytes(),
+    });
+    message
+}
+
+fn md5(data: &[u8]) -> [u8; 16] {
+    const SHIFTS: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5,
+        9, 14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10,
+        15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    let constants: [u32; 64] =
+        std::array::from_fn(|i| ((i as f64 + 1.0).sin().abs() * 4_294_967_296.0) as u32);
+
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    for block in pad(data, false).chunks_exact(64) {
+        let words: [u32; 16] = std::array::from_fn(|i| {
+            u32::from_le_bytes([
+                block[i * 4],
+                block[i * 4 + 1],
+                block[i * 4 + 2],
+                block[i * 4 + 3],
+            ])
+        });
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let f = { f.wrapping_add(a) }
+                .wrapping_add(constants[i])
+                .wrapping_add(words[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(SHIFTS[i]));
+        }
+        for (word, add) in state.iter_mut().zip([a, b, c, d]) {
+            *word = word.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0; 16];
+    for (out, word) in digest.chunks_exact_mut(4).zip(state) {
+        out.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    for block in pad(data, true).chunks_exact(64) {
+        let mut words = [0u32; 64];
+        for i in 0..16 {
+            words[i] = u32::from_be_bytes([
+                block[i * 4],
+                block[i * 4 + 1],
+                block[i * 4 + 2],
+                block[i * 4 + 3],
+            ]);
+        }
+        for i in 16..64 {
+            let s0 = words[i - 15].rotate_right(7)
+                ^ words[i - 15].rotate_right(18)
+                ^ (words[i - 15] >> 3);
+            let s1 = words[i - 2].rotate_right(17)
+                ^ words[i - 2].rotate_right(19)
+                ^ (words[i - 2] >> 10);
+            words[i] = { words[i - 16].wrapping_add(s0) }
+                .wrapping_add(words[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = { h.wrapping_add(s1) }
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(words[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+        for (word, add) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0; 32];
+    for (out, word) in digest.chunks_exact_mut(4).zip(state) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
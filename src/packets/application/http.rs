@@ -1,23 +1,44 @@
+use crate::packets::application::app_parser::{StreamContext, StreamParse};
+use crate::packets::fields::{Field, FieldValue};
+use crate::packets::shared_objs::Application;
+use crate::packets::tree::TreeNode;
+use crate::packets::{packet_traits::AppLayer, shared_objs::Protocol};
+use chrono::Duration;
 use core::fmt;
-use std::collections::HashMap;
+
+// a header block bigger than this isn't one we want to wait for
+const MAX_HEAD: usize = 64 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct HttpRequest {
     pub method: HttpRequestType,
     pub uri: String,
-    pub headers: HashMap<String, String>,
-    pub body: String,
+    pub version: String,
+    // in the order they were sent, names can repeat
+    pub headers: Vec<(String, String)>,
+    pub body: Box<[u8]>,
+    pub chunked: bool,
+    // the start line and headers on the wire, up to and including the empty line
+    pub head_length: usize,
+    pub response_in: Option<i32>,
 }
 
 #[derive(Debug, Clone)]
 pub struct HttpResponse {
+    pub version: String,
     pub status_code: u16,
     pub reason_phrase: String,
-    pub headers: HashMap<String, String>,
-    pub body: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Box<[u8]>,
+    pub chunked: bool,
+    pub head_length: usize,
+    // filled in once the response has been paired with its request
+    pub request_in: Option<i32>,
+    pub request_uri: Option<String>,
+    pub time_since_request: Option<Duration>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HttpRequestType {
     Head,
     Connect,
@@ -26,18 +47,580 @@ pub enum HttpRequestType {
     Put,
     Patch,
     Trace,
+    Delete,
+    Options,
+}
+
+impl HttpRequestType {
+    const ALL: [HttpRequestType; 9] = [
+        HttpRequestType::Head,
+        HttpRequestType::Connect,
+        HttpRequestType::Get,
+        HttpRequestType::Post,
+        HttpRequestType::Put,
+        HttpRequestType::Patch,
+        HttpRequestType::Trace,
+        HttpRequestType::Delete,
+        HttpRequestType::Options,
+    ];
+
+    pub fn from_token(token: &[u8]) -> Option<HttpRequestType> {
+        { HttpRequestType::ALL.into_iter() }.find(|method| method.token().as_bytes() == token)
+    }
+
+    pub fn token(&self) -> &'static str {
+        match self {
+            HttpRequestType::Head => "HEAD",
+            HttpRequestType::Connect => "CONNECT",
+            HttpRequestType::Get => "GET",
+            HttpRequestType::Post => "POST",
+            HttpRequestType::Put => "PUT",
+            HttpRequestType::Patch => "PATCH",
+            HttpRequestType::Trace => "TRACE",
+            HttpRequestType::Delete => "DELETE",
+            HttpRequestType::Options => "OPTIONS",
+        }
+    }
 }
 
 impl fmt::Display for HttpRequestType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            HttpRequestType::Head => write!(f, "head"),
-            HttpRequestType::Connect => write!(f, "connect"),
-            HttpRequestType::Get => write!(f, "get"),
-            HttpRequestType::Post => write!(f, "post"),
-            HttpRequestType::Put => write!(f, "put"),
-            HttpRequestType::Patch => write!(f, "patch"),
-            HttpRequestType::Trace => write!(f, "trace"),
+        write!(f, "{}", self.token())
+    }
+}
+
+impl HttpRequest {
+    // parses one request off the front of `data`
+    pub fn parse(data: &[u8]) -> StreamParse {
+        let (head, head_length) = match split_head(data) {
+            Ok(head) => head,
+            Err(incomplete) => return incomplete.into(),
+        };
+        let mut lines = head.split("\r\n");
+        let request_line = lines.next().unwrap_or_default();
+        let mut parts = request_line.split(' ');
+        let (Some(method), Some(uri), Some(version), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return StreamParse::Unknown;
+        };
+        let Some(method) = HttpRequestType::from_token(method.as_bytes()) else {
+            return StreamParse::Unknown;
+        };
+        if !version.starts_with("HTTP/1.") {
+            return StreamParse::Unknown;
+        }
+        let Some(headers) = parse_headers(lines) else {
+            return StreamParse::Unknown;
+        };
+
+        // requests without a length have no body
+        let length = content_length(&headers).unwrap_or(0);
+        let chunked = is_chunked(&headers);
+        let (body, body_length) =
+            match read_body(&data[head_length..], chunked, Some(length), false) {
+                Ok(body) => body,
+                Err(incomplete) => return incomplete.into(),
+            };
+
+        let request = HttpRequest {
+            method,
+            uri: uri.to_owned(),
+            version: version.to_owned(),
+            headers,
+            body,
+            chunked,
+            head_length,
+            response_in: None,
+        };
+        StreamParse::Message(Application::HttpRequest(request), head_length + body_length)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+impl HttpResponse {
+    // parses one response off the front of `data`
+    pub fn parse(data: &[u8], context: &StreamContext) -> StreamParse {
+        let (head, head_length) = match split_head(data) {
+            Ok(head) => head,
+            Err(incomplete) => return incomplete.into(),
+        };
+        let mut lines = head.split("\r\n");
+        let status_line = lines.next().unwrap_or_default();
+        let mut parts = status_line.splitn(3, ' ');
+        let (Some(version), Some(status_code)) = (parts.next(), parts.next()) else {
+            return StreamParse::Unknown;
+        };
+        let reason_phrase = parts.next().unwrap_or_default();
+        let Ok(status_code) = status_code.parse::<u16>() else {
+            return StreamParse::Unknown;
+        };
+        if !version.starts_with("HTTP/1.") {
+            return StreamParse::Unknown;
         }
+        let Some(headers) = parse_headers(lines) else {
+            return StreamParse::Unknown;
+        };
+
+        // informational, no content and not modified never carry a body, and
+        // neither does the answer to a HEAD, its headers describe the GET's
+        let no_body = matches!(status_code, 100..=199 | 204 | 304)
+            || (context.http_request == Some(HttpRequestType::Head) && status_code >= 200);
+        let chunked = !no_body && is_chunked(&headers);
+        let length = match no_body {
+            true => Some(0),
+            false => content_length(&headers),
+        };
+        let body = read_body(&data[head_length..], chunked, length, context.closed);
+        let (body, body_length) = match body {
+            Ok(body) => body,
+            Err(incomplete) => return incomplete.into(),
+        };
+
+        let response = HttpResponse {
+            version: version.to_owned(),
+            status_code,
+            reason_phrase: reason_phrase.to_owned(),
+            headers,
+            body,
+            chunked,
+            head_length,
+            request_in: None,
+            request_uri: None,
+            time_since_request: None,
+        };
+        StreamParse::Message(
+            Application::HttpResponse(response),
+            head_length + body_length,
+        )
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn is_informational(&self) -> bool {
+        (100..200).contains(&self.status_code)
+    }
+}
+
+// whether `data` starts like a request or status line, or could once more arrives
+pub fn detect(data: &[u8]) -> bool {
+    message_start(data).is_some()
+}
+
+// decides between a request and a response by how the data starts
+pub fn parse_stream(data: &[u8], context: &StreamContext) -> StreamParse {
+    match message_start(data) {
+        Some(MessageStart::Response) => HttpResponse::parse(data, context),
+        Some(MessageStart::Request) => HttpRequest::parse(data),
+        Some(MessageStart::Partial) => StreamParse::NeedMore,
+        None => StreamParse::Unknown,
+    }
+}
+
+impl AppLayer for HttpRequest {
+    fn get_summary(&self) -> String {
+        let mut summary = format!(
+            "method: {}
+uri: {}
+version: {}
+{}
+body length: {}{}",
+            self.method,
+            self.uri,
+            self.version,
+            format_headers(&self.headers),
+            self.body.len(),
+            if self.chunked { " (chunked)" } else { "" },
+        );
+        if let Some(response_in) = self.response_in {
+            summary.push_str(&format!("\nresponse in: {response_in}"));
+        }
+        summary
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::HTTP
+    }
+
+    fn info(&self) -> String {
+        format!("{} {} {}", self.method, self.uri, self.version)
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        self.body.to_vec()
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![
+            Field::new("http.request", true),
+            Field::new("http.request.method", self.method.token()),
+            Field::new("http.request.uri", self.uri.as_str()),
+            Field::new("http.request.version", self.version.as_str()),
+        ];
+        fields.extend(
+            self.header("Host")
+                .map(|host| Field::new("http.host", host)),
+        );
+        fields.extend(
+            { self.header("User-Agent") }.map(|agent| Field::new("http.user_agent", agent)),
+        );
+        fields
+            .extend({ self.response_in }.map(|frame| Field::new("http.response_in", frame as u32)));
+        fields.extend(message_fields(&self.headers, &self.body));
+        fields
+    }
+
+    fn tree(&self) -> TreeNode {
+        let start_line = format!("{} {} {}", self.method.token(), self.uri, self.version);
+        let mut children = message_tree(
+            &start_line,
+            &self.headers,
+            &self.body,
+            self.chunked,
+            self.head_length,
+        );
+        if let Some(response_in) = self.response_in {
+            children.push(TreeNode::new(format!("response in: {response_in}")));
+        }
+        let label = format!("{}, {}", self.protocol(), self.info());
+        TreeNode::at(label, 0, self.head_length).with_children(children)
+    }
+}
+
+impl AppLayer for HttpResponse {
+    fn get_summary(&self) -> String {
+        let mut summary = format!(
+            "version: {}
+status code: {}
+reason phrase: {}
+{}
+body length: {}{}",
+            self.version,
+            self.status_code,
+            self.reason_phrase,
+            format_headers(&self.headers),
+            self.body.len(),
+            if self.chunked { " (chunked)" } else { "" },
+        );
+        if let Some(request_in) = self.request_in {
+            summary.push_str(&format!("\nrequest in: {request_in}"));
+        }
+        if let Some(ref request_uri) = self.request_uri {
+            summary.push_str(&format!("\nrequest uri: {request_uri}"));
+        }
+        if let Some(time) = self.time_since_request {
+            let micros = time.num_microseconds().unwrap_or(i64::MAX);
+            summary.push_str(&format!(
+                "\ntime since request: {:.6} s",
+                micros as f64 / 1e6
+            ));
+        }
+        summary
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::HTTP
+    }
+
+    fn info(&self) -> String {
+        let mut info = format!(
+            "{} {} {}",
+            self.version, self.status_code, self.reason_phrase
+        );
+        if let Some(content_type) = self.header("Content-Type") {
+            info.push_str(&format!(" ({content_type})"));
+        }
+        info
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        self.body.to_vec()
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![
+            Field::new("http.response", true),
+            Field::new("http.response.version", self.version.as_str()),
+            Field::new("http.response.code", self.status_code),
+            Field::new("http.response.phrase", self.reason_phrase.as_str()),
+        ];
+        fields
+            .extend({ self.request_uri.as_deref() }.map(|uri| Field::new("http.request.uri", uri)));
+        fields.extend(
+            self.header("Server")
+                .map(|server| Field::new("http.server", server)),
+        );
+        fields.extend({ self.request_in }.map(|frame| Field::new("http.request_in", frame as u32)));
+        fields.extend(message_fields(&self.headers, &self.body));
+        fields
     }
+
+    fn tree(&self) -> TreeNode {
+        let start_line = match self.reason_phrase.is_empty() {
+            true => format!("{} {}", self.version, self.status_code),
+            false => format!(
+                "{} {} {}",
+                self.version, self.status_code, self.reason_phrase
+            ),
+        };
+        let mut children = message_tree(
+            &start_line,
+            &self.headers,
+            &self.body,
+            self.chunked,
+            self.head_length,
+        );
+        if let Some(request_in) = self.request_in {
+            children.push(TreeNode::new(format!("request in: {request_in}")));
+        }
+        if let Some(ref request_uri) = self.request_uri {
+            children.push(TreeNode::new(format!("request uri: {request_uri}")));
+        }
+        if let Some(time) = self.time_since_request {
+            let micros = time.num_microseconds().unwrap_or(i64::MAX);
+            children.push(TreeNode::new(format!(
+                "time since request: {:.6} s",
+                micros as f64 / 1e6
+            )));
+        }
+        let label = format!("{}, {}", self.protocol(), self.info());
+        TreeNode::at(label, 0, self.head_length).with_children(children)
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+// what the first token of the data says it is
+enum MessageStart {
+    Response,
+    Request,
+    // too short to tell yet
+    Partial,
+}
+
+fn message_start(data: &[u8]) -> Option<MessageStart> {
+    let token = match data.iter().position(|&b| b == b' ') {
+        Some(end) => &data[..end],
+        None => data,
+    };
+    let complete = token.len() < data.len();
+
+    if token.starts_with(b"HTTP/1.") {
+        Some(MessageStart::Response)
+    } else if complete && HttpRequestType::from_token(token).is_some() {
+        Some(MessageStart::Request)
+    } else if !complete && could_start_message(token) {
+        Some(MessageStart::Partial)
+    } else {
+        None
+    }
+}
+
+// why a message couldn't be taken off the stream
+enum Incomplete {
+    NeedMore,
+    Invalid,
+}
+
+impl From<Incomplete> for StreamParse {
+    fn from(incomplete: Incomplete) -> StreamParse {
+        match incomplete {
+            Incomplete::NeedMore => StreamParse::NeedMore,
+            Incomplete::Invalid => StreamParse::Unknown,
+        }
+    }
+}
+
+// the start line and headers as text, and how many bytes they took up
+fn split_head(data: &[u8]) -> Result<(&str, usize), Incomplete> {
+    let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") else {
+        return Err(match data.len() > MAX_HEAD {
+            true => Incomplete::Invalid,
+            false => Incomplete::NeedMore,
+        });
+    };
+    let head = std::str::from_utf8(&data[..end]).map_err(|_| Incomplete::Invalid)?;
+    Ok((head, end + 4))
+}
+
+fn parse_headers<'a>(lines: impl Iterator<Item = &'a str>) -> Option<Vec<(String, String)>> {
+    lines
+        .map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim().to_owned(), value.trim().to_owned()))
+        })
+        .collect()
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    { headers.iter() }
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn content_length(headers: &[(String, String)]) -> Option<usize> {
+    find_header(headers, "Content-Length")?.parse().ok()
+}
+
+fn is_chunked(headers: &[(String, String)]) -> bool {
+    find_header(headers, "Transfer-Encoding")
+        .is_some_and(|encoding| encoding.to_ascii_lowercase().contains("chunked"))
+}
+
+// the decoded body and how many bytes it took up on the wire. Without a length
+// the body runs to the end of the connection, so it's all here once `closed`.
+fn read_body(
+    data: &[u8],
+    chunked: bool,
+    length: Option<usize>,
+    closed: bool,
+) -> Result<(Box<[u8]>, usize), Incomplete> {
+    if chunked {
+        return read_chunked(data);
+    }
+    let length = match (length, closed) {
+        (Some(length), _) => length,
+        (None, true) => data.len(),
+        (None, false) => return Err(Incomplete::NeedMore),
+    };
+    match data.get(..length) {
+        Some(body) => Ok((body.into(), length)),
+        None => Err(Incomplete::NeedMore),
+    }
+}
+
+fn read_chunked(data: &[u8]) -> Result<(Box<[u8]>, usize), Incomplete> {
+    let mut body = Vec::new();
+    let mut position = 0;
+    loop {
+        let line = read_line(&data[position..])?;
+        position += line.len() + 2;
+        // chunk extensions come after a ';'
+        let size = line.split(|&b| b == b';').next().unwrap_or_default();
+        let size = std::str::from_utf8(size)
+            .ok()
+            .and_then(|size| usize::from_str_radix(size.trim(), 16).ok())
+            .ok_or(Incomplete::Invalid)?;
+
+        if size == 0 {
+            // trailers, then an empty line
+            loop {
+                let trailer = read_line(&data[position..])?;
+                position += trailer.len() + 2;
+                if trailer.is_empty() {
+                    return Ok((body.into_boxed_slice(), position));
+                }
+            }
+        }
+
+        let chunk = data
+            .get(position..position + size)
+            .ok_or(Incomplete::NeedMore)?;
+        body.extend_from_slice(chunk);
+        position += size;
+        match data.get(position..position + 2) {
+            Some(b"\r\n") => position += 2,
+            Some(_) => return Err(Incomplete::Invalid),
+            None => return Err(Incomplete::NeedMore),
+        }
+    }
+}
+
+// a line without its "\r\n"
+fn read_line(data: &[u8]) -> Result<&[u8], Incomplete> {
+    match data.windows(2).position(|w| w == b"\r\n") {
+        Some(end) => Ok(&data[..end]),
+        None if data.len() > MAX_HEAD => Err(Incomplete::Invalid),
+        None => Err(Incomplete::NeedMore),
+    }
+}
+
+// whether more data could still turn `token` into a method or status line
+fn could_start_message(token: &[u8]) -> bool {
+    b"HTTP/1.".starts_with(token) || { HttpRequestType::ALL.iter() }
+        .any(|method| method.token().as_bytes().starts_with(token))
+}
+
+// what requests and responses have in common
+fn message_fields(headers: &[(String, String)], body: &[u8]) -> Vec<Field> {
+    let mut fields = vec![];
+    if let Some(content_type) = find_header(headers, "Content-Type") {
+        fields.push(Field::new("http.content_type", content_type));
+    }
+    if let Some(length) = content_length(headers) {
+        fields.push(Field::new("http.content_length", length as u64));
+    }
+    if !body.is_empty() {
+        fields.push(Field::new(
+            "http.file_data",
+            FieldValue::Bytes(body.to_vec()),
+        ));
+    }
+    fields
+}
+
+// The start line, headers and body. The lines only get their own byte ranges
+// when they were written the usual way, "Name: value", and so add up to the
+// head's length, otherwise just the whole head has one.
+fn message_tree(
+    start_line: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+    chunked: bool,
+    head_length: usize,
+) -> Vec<TreeNode> {
+    let lines = { headers.iter() }
+        .map(|(name, value)| format!("{name}: {value}"))
+        .collect::<Vec<String>>();
+    let usual_length =
+        start_line.len() + { lines.iter() }.map(|line| line.len() + 2).sum::<usize>() + 4;
+
+    let mut children = vec![];
+    let mut headers = TreeNode::new("headers");
+    match usual_length == head_length {
+        true => {
+            children.push(TreeNode::at(start_line, 0, start_line.len()));
+            let mut offset = start_line.len() + 2;
+            for line in lines {
+                let length = line.len();
+                headers.children.push(TreeNode::at(line, offset, length));
+                offset += length + 2;
+            }
+            if offset > start_line.len() + 2 {
+                headers.range = Some(start_line.len() + 2..offset - 2);
+            }
+        }
+        false => {
+            children.push(TreeNode::new(start_line));
+            headers.children = lines.into_iter().map(TreeNode::new).collect();
+        }
+    }
+    children.push(headers);
+
+    let body_label = format!("body length: {}", body.len());
+    children.push(match chunked {
+        // the chunk sizes sit between the pieces on the wire
+        true => TreeNode::new(format!("{body_label} (chunked)")),
+        false => TreeNode::at(body_label, head_length, body.len()),
+    });
+    children
+}
+
+fn format_headers(headers: &[(String, String)]) -> String {
+    let headers = { headers.iter() }
+        .map(|(name, value)| format!("  {name}: {value}"))
+        .collect::<Vec<String>>()
+        .join("\n");
+    format!("headers:\n{headers}")
 }
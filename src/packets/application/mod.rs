@@ -1,4 +1,6 @@
 pub mod app_parser;
 pub mod dns;
+pub mod fingerprint;
 pub mod http;
+pub mod registry;
 pub mod tls;
@@ -0,0 +1,286 @@
+use crate::packets::application::app_parser::{StreamContext, StreamParse};
+use crate::packets::application::{dns, dns::DnsMessage, http, tls};
+use crate::packets::fields::FieldKind;
+use crate::packets::shared_objs::Application;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+// What carries the application data, dissectors have a separate port list for each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransportProtocol {
+    Udp,
+    Tcp,
+}
+
+impl fmt::Display for TransportProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportProtocol::Udp => write!(f, "udp"),
+            TransportProtocol::Tcp => write!(f, "tcp"),
+        }
+    }
+}
+
+// An application protocol dissector. It gets a payload when one of the ports
+// is in `ports`, when `detect` recognizes the data, or when the user decoded a
+// port as it. Port matches are tried before heuristics, each group in
+// `priority` order, and the first dissector that parses the data wins.
+pub trait AppDissector: Send + Sync {
+    // unique, it's what "decode as" rules refer to
+    fn name(&self) -> &str;
+
+    // well known ports
+    fn ports(&self, _transport: TransportProtocol) -> &[u16] {
+        &[]
+    }
+
+    // higher goes first
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    // heuristic for payloads on ports nobody claimed. For tcp `data` is the
+    // start of the next message, which may be only a few bytes of it.
+    fn detect(&self, _transport: TransportProtocol, _data: &[u8]) -> bool {
+        false
+    }
+
+    // a whole udp payload
+    fn parse_datagram(&self, _data: &[u8]) -> Option<Application> {
+        None
+    }
+
+    // the front of a reassembled tcp stream. A `StreamParse::Message` has to
+    // use between 1 and `data.len()` bytes, or the stream stops being dissected.
+    fn parse_stream(&self, _data: &[u8], _context: &StreamContext) -> StreamParse {
+        StreamParse::Unknown
+    }
+
+    // the fields its layers report, so display filters know them before any
+    // packet turns up. Names start with the protocol's lowercased name.
+    fn fields(&self) -> &[(&'static str, FieldKind)] {
+        &[]
+    }
+}
+
+// Decodes `port` as the dissector called `dissector` until cleared, on top of
+// whatever claims it by default. Only affects packets dissected afterwards.
+pub fn decode_as(transport: TransportProtocol, port: u16, dissector: &str) -> Result<(), String> {
+    let mut registry = write();
+    let dissector = { registry.dissectors.iter() }
+        .find(|d| d.name() == dissector)
+        .cloned()
+        .ok_or_else(|| format!("no dissector named {dissector}"))?;
+    registry.decode_as.insert((transport, port), dissector);
+    Ok(())
+}
+
+pub fn clear_decode_as(transport: TransportProtocol, port: u16) {
+    write().decode_as.remove(&(transport, port));
+}
+
+// (transport, port, dissector name), sorted
+pub fn decode_as_rules() -> Vec<(TransportProtocol, u16, String)> {
+    let registry = read();
+    let mut rules = { registry.decode_as.iter() }
+        .map(|(&(transport, port), dissector)| (transport, port, dissector.name().to_owned()))
+        .collect::<Vec<_>>();
+    rules.sort_by_key(|&(transport, port, _)| (transport as u8, port));
+    rules
+}
+
+pub fn dissector_names() -> Vec<String> {
+    { read().dissectors.iter() }
+        .map(|dissector| dissector.name().to_owned())
+        .collect()
+}
+
+// adds a dissector after the ones with the same or higher priority
+pub(crate) fn register(dissector: Arc<dyn AppDissector>) -> Result<(), String> {
+    let mut registry = write();
+    if { registry.dissectors.iter() }.any(|d| d.name() == dissector.name()) {
+        return Err(format!(
+            "a dissector named {} is already registered",
+            dissector.name()
+        ));
+    }
+    let index = { registry.dissectors.iter() }
+        .position(|d| d.priority() < dissector.priority())
+        .unwrap_or(registry.dissectors.len());
+    registry.dissectors.insert(index, dissector);
+    Ok(())
+}
+
+pub(crate) fn dissectors() -> Vec<Arc<dyn AppDissector>> {
+    read().dissectors.clone()
+}
+
+pub(crate) fn dissect_datagram(
+    source_port: u16,
+    destination_port: u16,
+    data: &[u8],
+) -> Application {
+    {
+        candidates(
+            TransportProtocol::Udp,
+            [source_port, destination_port],
+            data,
+        )
+    }
+    .find_map(|dissector| dissector.parse_datagram(data))
+    .unwrap_or_else(|| Application::Other(data.to_vec().into_boxed_slice()))
+}
+
+pub(crate) fn dissect_stream(
+    source_port: u16,
+    destination_port: u16,
+    data: &[u8],
+    context: &StreamContext,
+) -> StreamParse {
+    {
+        candidates(
+            TransportProtocol::Tcp,
+            [source_port, destination_port],
+            data,
+        )
+    }
+    .find_map(|dissector| match dissector.parse_stream(data, context) {
+        StreamParse::Unknown => None,
+        parse => Some(parse),
+    })
+    .unwrap_or(StreamParse::Unknown)
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+static REGISTRY: LazyLock<RwLock<Registry>> = LazyLock::new(|| {
+    RwLock::new(Registry {
+        dissectors: vec![Arc::new(Tls), Arc::new(Http), Arc::new(Dns)],
+        decode_as: HashMap::new(),
+    })
+});
+
+struct Registry {
+    // kept sorted by priority
+    dissectors: Vec<Arc<dyn AppDissector>>,
+    decode_as: HashMap<(TransportProtocol, u16), Arc<dyn AppDissector>>,
+}
+
+// the dissectors to try, in order
+fn candidates(
+    transport: TransportProtocol,
+    ports: [u16; 2],
+    data: &[u8],
+) -> impl Iterator<Item = Arc<dyn AppDissector>> + '_ {
+    // cloned out of the lock, a dissector may well parse nested messages
+    let registry = read();
+    let decode_as = { ports.iter() }
+        .find_map(|&port| registry.decode_as.get(&(transport, port)))
+        .cloned();
+    let (by_port, by_heuristic): (Vec<_>, Vec<_>) = match decode_as {
+        // a rule replaces everything else, it's there because the defaults got it wrong
+        Some(dissector) => (vec![dissector], vec![]),
+        None => { registry.dissectors.iter().cloned() }.partition(|dissector| {
+            { dissector.ports(transport).iter() }.any(|port| ports.contains(port))
+        }),
+    };
+    drop(registry);
+
+    let by_heuristic =
+        { by_heuristic.into_iter() }.filter(move |dissector| dissector.detect(transport, data));
+    by_port.into_iter().chain(by_heuristic)
+}
+
+fn read() -> RwLockReadGuard<'static, Registry> {
+    REGISTRY.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write() -> RwLockWriteGuard<'static, Registry> {
+    REGISTRY.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct Dns;
+
+impl AppDissector for Dns {
+    fn name(&self) -> &str {
+        "DNS"
+    }
+
+    // plain dns, mdns and llmnr all share the format
+    fn ports(&self, transport: TransportProtocol) -> &[u16] {
+        match transport {
+            TransportProtocol::Udp => &[53, 5353, 5355],
+            TransportProtocol::Tcp => &[53],
+        }
+    }
+
+    fn parse_datagram(&self, data: &[u8]) -> Option<Application> {
+        DnsMessage::new(data).ok().map(Application::Dns)
+    }
+
+    fn parse_stream(&self, data: &[u8], _context: &StreamContext) -> StreamParse {
+        dns::parse_stream(data)
+    }
+}
+
+struct Http;
+
+impl AppDissector for Http {
+    fn name(&self) -> &str {
+        "HTTP"
+    }
+
+    fn ports(&self, transport: TransportProtocol) -> &[u16] {
+        match transport {
+            TransportProtocol::Udp => &[],
+            TransportProtocol::Tcp => &[80, 8000, 8008, 8080],
+        }
+    }
+
+    fn priority(&self) -> i32 {
+        10
+    }
+
+    fn detect(&self, transport: TransportProtocol, data: &[u8]) -> bool {
+        transport == TransportProtocol::Tcp && http::detect(data)
+    }
+
+    fn parse_stream(&self, data: &[u8], context: &StreamContext) -> StreamParse {
+        http::parse_stream(data, context)
+    }
+}
+
+struct Tls;
+
+impl AppDissector for Tls {
+    fn name(&self) -> &str {
+        "TLS"
+    }
+
+    fn ports(&self, transport: TransportProtocol) -> &[u16] {
+        match transport {
+            TransportProtocol::Udp => &[],
+            TransportProtocol::Tcp => &[443, 465, 636, 853, 993, 995, 8443],
+        }
+    }
+
+    // tls records can't be mistaken for http text, so they're checked first
+    fn priority(&self) -> i32 {
+        20
+    }
+
+    fn detect(&self, transport: TransportProtocol, data: &[u8]) -> bool {
+        transport == TransportProtocol::Tcp && tls::detect(data)
+    }
+
+    fn parse_stream(&self, data: &[u8], _context: &StreamContext) -> StreamParse {
+        tls::parse_stream(data)
+    }
+}
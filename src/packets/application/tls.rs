@@ -1,25 +1,116 @@
+use crate::packets::application::app_parser::StreamParse;
+use crate::packets::fields::Field;
+use crate::packets::shared_objs::Application;
+use crate::packets::tree::TreeNode;
+use crate::packets::{packet_traits::AppLayer, shared_objs::Protocol};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use core::fmt;
 
+// 2^14 plus the most a cipher is allowed to add
+const MAX_RECORD: usize = 16_384 + 2_048;
+// a handshake message claiming more than this is really encrypted data
+const MAX_HANDSHAKE: usize = 1024 * 1024;
+
+// One TLS record, or several when a handshake message is split across records
 #[derive(Debug, Clone)]
 pub struct Tls {
-    pub version: TlsVersion,
-    pub cipher_suite: CipherSuite,
-    pub server_certificates: Vec<Certificate>,
-    pub client_certificates: Vec<Certificate>,
-    pub is_handshake_complete: bool,
+    pub content_type: TlsContentType,
+    pub record_version: u16,
+    pub records: usize,
+    pub messages: Vec<TlsMessage>,
+    // the record contents, still encrypted for application data
+    pub fragment: Box<[u8]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsContentType {
+    ChangeCipherSpec,
+    Alert,
+    Handshake,
+    ApplicationData,
+    Heartbeat,
+}
+
+#[derive(Debug, Clone)]
+pub enum TlsMessage {
+    ClientHello(ClientHello),
+    ServerHello(ServerHello),
+    Certificate(Vec<Certificate>),
+    // any other handshake message, by type
+    Handshake(u8),
+    EncryptedHandshake,
+    Alert(TlsAlert),
+    EncryptedAlert,
+    ChangeCipherSpec,
+    ApplicationData,
+    Heartbeat,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientHello {
+    pub version: u16,
+    pub session_id: Box<[u8]>,
+    pub cipher_suites: Vec<u16>,
+    pub compression_methods: Vec<u8>,
+    pub extensions: Vec<TlsExtension>,
+    pub server_name: Option<String>,
+    pub alpn: Vec<String>,
+    pub supported_versions: Vec<u16>,
+    pub supported_groups: Vec<u16>,
+    pub ec_point_formats: Vec<u8>,
+    pub signature_algorithms: Vec<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerHello {
+    pub version: u16,
+    pub session_id: Box<[u8]>,
+    pub cipher_suite: u16,
+    pub compression_method: u8,
+    pub extensions: Vec<TlsExtension>,
+    pub alpn: Option<String>,
+    // TLS 1.3 negotiates the version in an extension, the version field says 1.2
+    pub selected_version: Option<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsExtension {
+    pub extension_type: u16,
+    pub data: Box<[u8]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsAlert {
+    pub level: u8,
+    pub description: u8,
 }
 
 #[derive(Debug, Clone)]
 pub enum TlsVersion {
+    Ssl3_0,
     Tls1_0,
     Tls1_1,
     Tls1_2,
     Tls1_3,
 }
 
+impl TlsVersion {
+    pub fn from_u16(version: u16) -> Option<TlsVersion> {
+        match version {
+            0x0300 => Some(TlsVersion::Ssl3_0),
+            0x0301 => Some(TlsVersion::Tls1_0),
+            0x0302 => Some(TlsVersion::Tls1_1),
+            0x0303 => Some(TlsVersion::Tls1_2),
+            0x0304 => Some(TlsVersion::Tls1_3),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for TlsVersion {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            TlsVersion::Ssl3_0 => write!(f, "ssl v3.0"),
             TlsVersion::Tls1_0 => write!(f, "tls v1.0"),
             TlsVersion::Tls1_1 => write!(f, "tls v1.1"),
             TlsVersion::Tls1_2 => write!(f, "tls v1.2"),
@@ -30,10 +121,66 @@ impl fmt::Display for TlsVersion {
 
 #[derive(Debug, Clone)]
 pub struct CipherSuite {
+    pub id: u16,
     pub name: String,
-    pub key_exchange_algorithm: KeyExchangeAlgorithm,
-    pub encryption_algorithm: EncryptionAlgorithm,
-    pub hash_algorithm: HashAlgorithm,
+    // TLS 1.3 suites leave the key exchange to extensions
+    pub key_exchange_algorithm: Option<KeyExchangeAlgorithm>,
+    pub encryption_algorithm: Option<EncryptionAlgorithm>,
+    pub hash_algorithm: Option<HashAlgorithm>,
+}
+
+impl CipherSuite {
+    pub fn from_id(id: u16) -> CipherSuite {
+        let name = match cipher_suite_name(id) {
+            Some(name) => name.to_owned(),
+            None if is_grease(id) => "Reserved (GREASE)".to_owned(),
+            None => format!("Unknown ({id:#06x})"),
+        };
+
+        let key_exchange_algorithm = if name.contains("_ECDHE_") || name.contains("_ECDH_") {
+            Some(KeyExchangeAlgorithm::Ecdh)
+        } else if name.contains("_DHE_") || name.contains("_DH_") {
+            Some(KeyExchangeAlgorithm::DiffieHellman)
+        } else if name.starts_with("TLS_RSA_") {
+            Some(KeyExchangeAlgorithm::Rsa)
+        } else {
+            None
+        };
+        let encryption_algorithm = if name.contains("AES_128") {
+            Some(EncryptionAlgorithm::Aes128)
+        } else if name.contains("AES_256") {
+            Some(EncryptionAlgorithm::Aes256)
+        } else if name.contains("CHACHA20") {
+            Some(EncryptionAlgorithm::Chacha20)
+        } else {
+            None
+        };
+        let hash_algorithm = if name.ends_with("_SHA256") {
+            Some(HashAlgorithm::Sha256)
+        } else if name.ends_with("_SHA384") {
+            Some(HashAlgorithm::Sha384)
+        } else if name.ends_with("_SHA512") {
+            Some(HashAlgorithm::Sha512)
+        } else if name.ends_with("_SHA") {
+            Some(HashAlgorithm::Sha1)
+        } else {
+            None
+        };
+
+        CipherSuite {
+            id,
+            name,
+            key_exchange_algorithm,
+            encryption_algorithm,
+            hash_algorithm,
+        }
+    }
+}
+
+impl fmt::Display for CipherSuite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:#06x})", self.name, self.id)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -46,8 +193,8 @@ pub struct Certificate {
 
 #[derive(Debug, Clone)]
 pub struct ValidityPeriod {
-    pub not_before: String, // or use a date-time
-    pub not_after: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone)]
@@ -62,7 +209,7 @@ impl fmt::Display for KeyExchangeAlgorithm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             KeyExchangeAlgorithm::Rsa => write!(f, "rsa"),
-            KeyExchangeAlgorithm::DiffieHellman => write!(f, "diffe-hellman"),
+            KeyExchangeAlgorithm::DiffieHellman => write!(f, "diffie-hellman"),
             KeyExchangeAlgorithm::Ecdh => write!(f, "ecdh"),
         }
     }
@@ -88,6 +235,7 @@ impl fmt::Display for EncryptionAlgorithm {
 
 #[derive(Debug, Clone)]
 pub enum HashAlgorithm {
+    Sha1,
     Sha256,
     Sha384,
     Sha512,
@@ -97,9 +245,988 @@ pub enum HashAlgorithm {
 impl fmt::Display for HashAlgorithm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            HashAlgorithm::Sha1 => write!(f, "sha1"),
             HashAlgorithm::Sha256 => write!(f, "sha256"),
             HashAlgorithm::Sha384 => write!(f, "sha384"),
             HashAlgorithm::Sha512 => write!(f, "sha512"),
         }
     }
 }
+
+impl Tls {
+    // parses one record off the front of `data`, plus any records after it
+    // that a handshake message continues into
+    pub fn parse(data: &[u8]) -> StreamParse {
+        let (content_type, record_version, fragment) = match read_record(data) {
+            Ok(record) => record,
+            Err(incomplete) => return incomplete.into(),
+        };
+        let mut used = 5 + fragment.len();
+
+        let (records, fragment, messages) = match content_type {
+            TlsContentType::Handshake => match read_handshake(data) {
+                Ok((records, handshake, handshake_used)) => {
+                    used = handshake_used;
+                    let messages = parse_handshake_messages(&handshake);
+                    (records, handshake, messages)
+                }
+                Err(Incomplete::NeedMore) => return StreamParse::NeedMore,
+                // doesn't parse as a handshake, so the keys have already changed
+                Err(Incomplete::Invalid) => {
+                    (1, fragment.into(), vec![TlsMessage::EncryptedHandshake])
+                }
+            },
+            TlsContentType::Alert => {
+                let message = match fragment {
+                    [level, description] => TlsMessage::Alert(TlsAlert {
+                        level: *level,
+                        description: *description,
+                    }),
+                    _ => TlsMessage::EncryptedAlert,
+                };
+                (1, fragment.into(), vec![message])
+            }
+            TlsContentType::ChangeCipherSpec => {
+                (1, fragment.into(), vec![TlsMessage::ChangeCipherSpec])
+            }
+            TlsContentType::ApplicationData => {
+                (1, fragment.into(), vec![TlsMessage::ApplicationData])
+            }
+            TlsContentType::Heartbeat => (1, fragment.into(), vec![TlsMessage::Heartbeat]),
+        };
+
+        let tls = Tls {
+            content_type,
+            record_version,
+            records,
+            messages,
+            fragment,
+        };
+        StreamParse::Message(Application::Tls(tls), used)
+    }
+
+    pub fn client_hello(&self) -> Option<&ClientHello> {
+        self.messages.iter().find_map(|message| match message {
+            TlsMessage::ClientHello(hello) => Some(hello),
+            _ => None,
+        })
+    }
+
+    pub fn server_hello(&self) -> Option<&ServerHello> {
+        self.messages.iter().find_map(|message| match message {
+            TlsMessage::ServerHello(hello) => Some(hello),
+            _ => None,
+        })
+    }
+}
+
+impl ClientHello {
+    fn parse(body: &[u8]) -> Option<ClientHello> {
+        let mut reader = Reader::new(body);
+        let version = reader.u16()?;
+        reader.bytes(32)?;
+        let session_id = reader.vec8()?.into();
+        let cipher_suites = u16_list(reader.vec16()?);
+        let compression_methods = reader.vec8()?.to_vec();
+        // SSLv3 and TLS 1.0 clients, and plenty of scanners, send none at all
+        let extensions = match reader.is_empty() {
+            true => Vec::new(),
+            false => parse_extensions(&mut reader)?,
+        };
+
+        let mut hello = ClientHello {
+            version,
+            session_id,
+            cipher_suites,
+            compression_methods,
+            extensions,
+            server_name: None,
+            alpn: Vec::new(),
+            supported_versions: Vec::new(),
+            supported_groups: Vec::new(),
+            ec_point_formats: Vec::new(),
+            signature_algorithms: Vec::new(),
+        };
+        for extension in &hello.extensions {
+            let mut data = Reader::new(&extension.data);
+            match extension.extension_type {
+                EXTENSION_SERVER_NAME => hello.server_name = parse_server_name(&mut data),
+                EXTENSION_ALPN => hello.alpn = parse_alpn(&mut data).unwrap_or_default(),
+                EXTENSION_SUPPORTED_VERSIONS => {
+                    hello.supported_versions = u16_list(data.vec8().unwrap_or_default())
+                }
+                EXTENSION_SUPPORTED_GROUPS => {
+                    hello.supported_groups = u16_list(data.vec16().unwrap_or_default())
+                }
+                EXTENSION_EC_POINT_FORMATS => {
+                    hello.ec_point_formats = data.vec8().unwrap_or_default().to_vec()
+                }
+                EXTENSION_SIGNATURE_ALGORITHMS => {
+                    hello.signature_algorithms = u16_list(data.vec16().unwrap_or_default())
+                }
+                _ => {}
+            }
+        }
+        Some(hello)
+    }
+
+    // the highest version offered, not counting GREASE
+    pub fn highest_version(&self) -> u16 {
+        { self.supported_versions.iter().copied() }
+            .filter(|version| !is_grease(*version))
+            .max()
+            .unwrap_or(self.version)
+    }
+}
+
+impl ServerHello {
+    fn parse(body: &[u8]) -> Option<ServerHello> {
+        let mut reader = Reader::new(body);
+        let version = reader.u16()?;
+        reader.bytes(32)?;
+        let session_id = reader.vec8()?.into();
+        let cipher_suite = reader.u16()?;
+        let compression_method = reader.u8()?;
+        // extensions are optional before TLS 1.3
+        let extensions = match reader.is_empty() {
+            true => Vec::new(),
+            false => parse_extensions(&mut reader)?,
+        };
+
+        let mut hello = ServerHello {
+            version,
+            session_id,
+            cipher_suite,
+            compression_method,
+            extensions,
+            alpn: None,
+            selected_version: None,
+        };
+        for extension in &hello.extensions {
+            let mut data = Reader::new(&extension.data);
+            match extension.extension_type {
+                EXTENSION_ALPN => {
+                    hello.alpn = parse_alpn(&mut data).and_then(|alpn| alpn.into_iter().next())
+                }
+                EXTENSION_SUPPORTED_VERSIONS => hello.selected_version = data.u16(),
+                _ => {}
+            }
+        }
+        Some(hello)
+    }
+
+    pub fn negotiated_version(&self) -> u16 {
+        self.selected_version.unwrap_or(self.version)
+    }
+}
+
+impl TlsExtension {
+    pub fn name(&self) -> String {
+        let name = match self.extension_type {
+            EXTENSION_SERVER_NAME => "server_name",
+            5 => "status_request",
+            EXTENSION_SUPPORTED_GROUPS => "supported_groups",
+            EXTENSION_EC_POINT_FORMATS => "ec_point_formats",
+            EXTENSION_SIGNATURE_ALGORITHMS => "signature_algorithms",
+            EXTENSION_ALPN => "application_layer_protocol_negotiation",
+            18 => "signed_certificate_timestamp",
+            21 => "padding",
+            22 => "encrypt_then_mac",
+            23 => "extended_master_secret",
+            27 => "compress_certificate",
+            35 => "session_ticket",
+            41 => "pre_shared_key",
+            42 => "early_data",
+            EXTENSION_SUPPORTED_VERSIONS => "supported_versions",
+            45 => "psk_key_exchange_modes",
+            51 => "key_share",
+            0xff01 => "renegotiation_info",
+            extension_type if is_grease(extension_type) => "Reserved (GREASE)",
+            extension_type => return format!("Unknown ({extension_type})"),
+        };
+        name.to_owned()
+    }
+}
+
+impl TlsAlert {
+    pub fn level_name(&self) -> &str {
+        match self.level {
+            1 => "Warning",
+            2 => "Fatal",
+            _ => "Unknown",
+        }
+    }
+
+    pub fn description_name(&self) -> &str {
+        match self.description {
+            0 => "Close Notify",
+            10 => "Unexpected Message",
+            20 => "Bad Record MAC",
+            21 => "Decryption Failed",
+            22 => "Record Overflow",
+            40 => "Handshake Failure",
+            42 => "Bad Certificate",
+            43 => "Unsupported Certificate",
+            44 => "Certificate Revoked",
+            45 => "Certificate Expired",
+            46 => "Certificate Unknown",
+            47 => "Illegal Parameter",
+            48 => "Unknown CA",
+            49 => "Access Denied",
+            50 => "Decode Error",
+            51 => "Decrypt Error",
+            70 => "Protocol Version",
+            71 => "Insufficient Security",
+            80 => "Internal Error",
+            86 => "Inappropriate Fallback",
+            90 => "User Canceled",
+            100 => "No Renegotiation",
+            109 => "Missing Extension",
+            110 => "Unsupported Extension",
+            112 => "Unrecognized Name",
+            116 => "Certificate Required",
+            120 => "No Application Protocol",
+            _ => "Unknown",
+        }
+    }
+}
+
+impl TlsMessage {
+    pub fn name(&self) -> String {
+        let name = match self {
+            TlsMessage::ClientHello(_) => "ClientHello",
+            TlsMessage::ServerHello(_) => "ServerHello",
+            TlsMessage::Certificate(_) => "Certificate",
+            TlsMessage::Handshake(handshake_type) => handshake_name(*handshake_type),
+            TlsMessage::EncryptedHandshake => "Encrypted Handshake Message",
+            TlsMessage::Alert(alert) => {
+                return format!(
+                    "Alert ({}, {})",
+                    alert.level_name(),
+                    alert.description_name()
+                )
+            }
+            TlsMessage::EncryptedAlert => "Encrypted Alert",
+            TlsMessage::ChangeCipherSpec => "Change Cipher Spec",
+            TlsMessage::ApplicationData => "Application Data",
+            TlsMessage::Heartbeat => "Heartbeat",
+        };
+        name.to_owned()
+    }
+
+    fn summary(&self) -> String {
+        match self {
+            TlsMessage::ClientHello(hello) => {
+                let cipher_suites = { hello.cipher_suites.iter() }
+                    .map(|id| format!("  {}", CipherSuite::from_id(*id)))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                format!(
+                    "ClientHello
+version: {}
+session id length: {}
+server name: {}
+alpn: {}
+supported versions: {}
+cipher suites ({}):
+{cipher_suites}
+extensions: {}
+ja3: {}
+ja3 hash: {}
+ja4: {}",
+                    format_version(hello.version),
+                    hello.session_id.len(),
+                    hello.server_name.as_deref().unwrap_or("none"),
+                    hello.alpn.join(", "),
+                    { hello.supported_versions.iter() }
+                        .map(|version| format_version(*version))
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    hello.cipher_suites.len(),
+                    format_extensions(&hello.extensions),
+                    hello.ja3(),
+                    hello.ja3_hash(),
+                    hello.ja4(),
+                )
+            }
+            TlsMessage::ServerHello(hello) => {
+                let cipher_suite = CipherSuite::from_id(hello.cipher_suite);
+                format!(
+                    "ServerHello
+version: {}
+session id length: {}
+cipher suite: {cipher_suite}
+key exchange: {}
+encryption: {}
+hash: {}
+alpn: {}
+extensions: {}
+ja3s: {}
+ja3s hash: {}",
+                    format_version(hello.negotiated_version()),
+                    hello.session_id.len(),
+                    format_optional(&cipher_suite.key_exchange_algorithm),
+                    format_optional(&cipher_suite.encryption_algorithm),
+                    format_optional(&cipher_suite.hash_algorithm),
+                    hello.alpn.as_deref().unwrap_or("none"),
+                    format_extensions(&hello.extensions),
+                    hello.ja3s(),
+                    hello.ja3s_hash(),
+                )
+            }
+            TlsMessage::Certificate(certificates) => {
+                let mut summary = format!("Certificate ({} in chain)", certificates.len());
+                for certificate in certificates {
+                    summary.push_str(&format!(
+                        "
+  subject: {}
+  issuer: {}
+  not before: {}
+  not after: {}",
+                        certificate.subject,
+                        certificate.issuer,
+                        certificate.validity_period.not_before,
+                        certificate.validity_period.not_after,
+                    ));
+                }
+                summary
+            }
+            message => message.name(),
+        }
+    }
+}
+
+// decides whether `data` starts with a TLS record
+pub fn parse_stream(data: &[u8]) -> StreamParse {
+    match detect(data) {
+        true => Tls::parse(data),
+        false => StreamParse::Unknown,
+    }
+}
+
+// a record header so far: known content type, then a 3.x version
+pub fn detect(data: &[u8]) -> bool {
+    match data {
+        [content_type, version @ ..] => {
+            content_type_from_u8(*content_type).is_some()
+                && version.first().is_none_or(|&major| major == 3)
+        }
+        [] => false,
+    }
+}
+
+impl AppLayer for Tls {
+    fn get_summary(&self) -> String {
+        let messages = { self.messages.iter() }
+            .map(TlsMessage::summary)
+            .collect::<Vec<String>>()
+            .join("\n\n");
+        format!(
+            "content type: {}
+record version: {}
+records: {}
+length: {}
+
+{messages}",
+            content_type_name(self.content_type),
+            format_version(self.record_version),
+            self.records,
+            self.fragment.len(),
+        )
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::TLS
+    }
+
+    fn info(&self) -> String {
+        { self.messages.iter() }
+            .map(|message| match message {
+                TlsMessage::ClientHello(ClientHello {
+                    server_name: Some(server_name),
+                    ..
+                }) => format!("ClientHello {server_name}"),
+                message => message.name(),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        self.fragment.to_vec()
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![
+            Field::new(
+                "tls.record.content_type",
+                content_type_number(self.content_type),
+            ),
+            Field::new("tls.record.version", self.record_version),
+        ];
+        for message in &self.messages {
+            match message {
+                TlsMessage::ClientHello(hello) => {
+                    fields.push(Field::new("tls.handshake.type", 1u8));
+                    fields.push(Field::new("tls.handshake.version", hello.version));
+                    for cipher_suite in &hello.cipher_suites {
+                        fields.push(Field::new("tls.handshake.ciphersuite", *cipher_suite));
+                    }
+                    for extension in &hello.extensions {
+                        fields.push(Field::new(
+                            "tls.handshake.extension.type",
+                            extension.extension_type,
+                        ));
+                    }
+                    if let Some(server_name) = &hello.server_name {
+                        fields.push(Field::new(
+                            "tls.handshake.extensions_server_name",
+                            server_name.as_str(),
+                        ));
+                    }
+                    for protocol in &hello.alpn {
+                        fields.push(Field::new(
+                            "tls.handshake.extensions_alpn_str",
+                            protocol.as_str(),
+                        ));
+                    }
+                    for version in &hello.supported_versions {
+                        fields.push(Field::new(
+                            "tls.handshake.extensions.supported_version",
+                            *version,
+                        ));
+                    }
+                    fields.push(Field::new("tls.handshake.ja3", hello.ja3_hash()));
+                    fields.push(Field::new("tls.handshake.ja3_full", hello.ja3()));
+                    fields.push(Field::new("tls.handshake.ja4", hello.ja4()));
+                }
+                TlsMessage::ServerHello(hello) => {
+                    fields.push(Field::new("tls.handshake.type", 2u8));
+                    fields.push(Field::new("tls.handshake.version", hello.version));
+                    fields.push(Field::new("tls.handshake.ciphersuite", hello.cipher_suite));
+                    for extension in &hello.extensions {
+                        fields.push(Field::new(
+                            "tls.handshake.extension.type",
+                            extension.extension_type,
+                        ));
+                    }
+                    if let Some(protocol) = &hello.alpn {
+                        fields.push(Field::new(
+                            "tls.handshake.extensions_alpn_str",
+                            protocol.as_str(),
+                        ));
+                    }
+                    if let Some(version) = hello.selected_version {
+                        fields.push(Field::new(
+                            "tls.handshake.extensions.supported_version",
+                            version,
+                        ));
+                    }
+                    fields.push(Field::new("tls.handshake.ja3s", hello.ja3s_hash()));
+                    fields.push(Field::new("tls.handshake.ja3s_full", hello.ja3s()));
+                }
+                TlsMessage::Certificate(certificates) => {
+                    fields.push(Field::new("tls.handshake.type", 11u8));
+                    for certificate in certificates {
+                        fields.push(Field::new(
+                            "tls.handshake.certificate.subject",
+                            certificate.subject.as_str(),
+                        ));
+                        fields.push(Field::new(
+                            "tls.handshake.certificate.issuer",
+                            certificate.issuer.as_str(),
+                        ));
+                    }
+                }
+                TlsMessage::Handshake(handshake_type) => {
+                    fields.push(Field::new("tls.handshake.type", *handshake_type));
+                }
+                TlsMessage::Alert(alert) => {
+                    fields.push(Field::new("tls.alert_message.level", alert.level));
+                    fields.push(Field::new("tls.alert_message.desc", alert.description));
+                }
+                _ => {}
+            }
+        }
+        fields
+    }
+
+    // the messages only have byte ranges while they all sit in the one record,
+    // joined records have headers in between
+    fn tree(&self) -> TreeNode {
+        let mut children = vec![
+            TreeNode::at(
+                format!("content type: {}", content_type_name(self.content_type)),
+                0,
+                1,
+            ),
+            TreeNode::at(
+                format!("record version: {}", format_version(self.record_version)),
+                1,
+                2,
+            ),
+            TreeNode::at(format!("length: {}", self.fragment.len()), 3, 2),
+        ];
+        if self.records > 1 {
+            children.push(TreeNode::new(format!("records: {}", self.records)));
+        }
+
+        let mut offset = 0;
+        for message in &self.messages {
+            let summary = message.summary();
+            // the first line names the message
+            let (name, rest) = summary.split_once('\n').unwrap_or((&summary, ""));
+            let mut node = TreeNode::from_summary(name, rest);
+            let length = match message {
+                TlsMessage::EncryptedHandshake => self.fragment.len(),
+                // a type, then a 3 byte length
+                _ if self.content_type == TlsContentType::Handshake => {
+                    match self.fragment.get(offset + 1..offset + 4) {
+                        Some(&[a, b, c]) => 4 + u32::from_be_bytes([0, a, b, c]) as usize,
+                        _ => 0,
+                    }
+                }
+                _ => self.fragment.len(),
+            };
+            if self.records == 1 {
+                node.range = Some(5 + offset..5 + offset + length);
+            }
+            offset += length;
+            children.push(node);
+        }
+
+        let label = format!("{}, {}", self.protocol(), self.info());
+        TreeNode::at(label, 0, 5).with_children(children)
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+pub(crate) const EXTENSION_SERVER_NAME: u16 = 0;
+const EXTENSION_SUPPORTED_GROUPS: u16 = 10;
+const EXTENSION_EC_POINT_FORMATS: u16 = 11;
+const EXTENSION_SIGNATURE_ALGORITHMS: u16 = 13;
+pub(crate) const EXTENSION_ALPN: u16 = 16;
+const EXTENSION_SUPPORTED_VERSIONS: u16 = 43;
+
+// why a record couldn't be taken off the stream
+enum Incomplete {
+    NeedMore,
+    Invalid,
+}
+
+impl From<Incomplete> for StreamParse {
+    fn from(incomplete: Incomplete) -> StreamParse {
+        match incomplete {
+            Incomplete::NeedMore => StreamParse::NeedMore,
+            Incomplete::Invalid => StreamParse::Unknown,
+        }
+    }
+}
+
+fn content_type_from_u8(content_type: u8) -> Option<TlsContentType> {
+    match content_type {
+        20 => Some(TlsContentType::ChangeCipherSpec),
+        21 => Some(TlsContentType::Alert),
+        22 => Some(TlsContentType::Handshake),
+        23 => Some(TlsContentType::ApplicationData),
+        24 => Some(TlsContentType::Heartbeat),
+        _ => None,
+    }
+}
+
+fn content_type_number(content_type: TlsContentType) -> u8 {
+    match content_type {
+        TlsContentType::ChangeCipherSpec => 20,
+        TlsContentType::Alert => 21,
+        TlsContentType::Handshake => 22,
+        TlsContentType::ApplicationData => 23,
+        TlsContentType::Heartbeat => 24,
+    }
+}
+
+fn content_type_name(content_type: TlsContentType) -> &'static str {
+    match content_type {
+        TlsContentType::ChangeCipherSpec => "Change Cipher Spec (20)",
+        TlsContentType::Alert => "Alert (21)",
+        TlsContentType::Handshake => "Handshake (22)",
+        TlsContentType::ApplicationData => "Application Data (23)",
+        TlsContentType::Heartbeat => "Heartbeat (24)",
+    }
+}
+
+// (content type, version, fragment) of the record at the front of `data`
+fn read_record(data: &[u8]) -> Result<(TlsContentType, u16, &[u8]), Incomplete> {
+    let [content_type, major, minor, length_hi, length_lo, rest @ ..] = data else {
+        return Err(Incomplete::NeedMore);
+    };
+    let content_type = content_type_from_u8(*content_type).ok_or(Incomplete::Invalid)?;
+    let length = u16::from_be_bytes([*length_hi, *length_lo]) as usize;
+    if *major != 3 || *minor > 4 || length > MAX_RECORD {
+        return Err(Incomplete::Invalid);
+    }
+    let fragment = rest.get(..length).ok_or(Incomplete::NeedMore)?;
+    Ok((content_type, u16::from_be_bytes([*major, *minor]), fragment))
+}
+
+// Joins handshake records until every message in them is whole. Returns how
+// many records that took, the joined messages and the bytes used.
+fn read_handshake(data: &[u8]) -> Result<(usize, Box<[u8]>, usize), Incomplete> {
+    let mut handshake = Vec::new();
+    let mut used = 0;
+    let mut records = 0;
+    loop {
+        match read_record(&data[used..]) {
+            Ok((TlsContentType::Handshake, _, fragment)) => {
+                handshake.extend_from_slice(fragment);
+                used += 5 + fragment.len();
+                records += 1;
+            }
+            // the message is cut off by something that isn't more handshake
+            Ok(_) | Err(Incomplete::Invalid) => return Err(Incomplete::Invalid),
+            Err(Incomplete::NeedMore) => return Err(Incomplete::NeedMore),
+        }
+
+        match handshake_complete(&handshake) {
+            Some(true) => return Ok((records, handshake.into(), used)),
+            Some(false) => {}
+            None => return Err(Incomplete::Invalid),
+        }
+    }
+}
+
+// whether the messages in `handshake` end exactly at its end, None when they
+// don't look like handshake messages at all
+fn handshake_complete(handshake: &[u8]) -> Option<bool> {
+    let mut reader = Reader::new(handshake);
+    while !reader.is_empty() {
+        let handshake_type = reader.u8()?;
+        let Some(length) = reader.u24() else {
+            return Some(false);
+        };
+        if !is_handshake_type(handshake_type) || length > MAX_HANDSHAKE {
+            return None;
+        }
+        if reader.bytes(length).is_none() {
+            return Some(false);
+        }
+    }
+    Some(true)
+}
+
+fn parse_handshake_messages(handshake: &[u8]) -> Vec<TlsMessage> {
+    let mut reader = Reader::new(handshake);
+    let mut messages = Vec::new();
+    while let (Some(handshake_type), Some(body)) = (reader.u8(), reader.vec24()) {
+        let message = match handshake_type {
+            1 => ClientHello::parse(body).map(TlsMessage::ClientHello),
+            2 => ServerHello::parse(body).map(TlsMessage::ServerHello),
+            11 => parse_certificates(body).map(TlsMessage::Certificate),
+            _ => None,
+        };
+        messages.push(message.unwrap_or(TlsMessage::Handshake(handshake_type)));
+    }
+    messages
+}
+
+fn is_handshake_type(handshake_type: u8) -> bool {
+    matches!(
+        handshake_type,
+        0 | 1 | 2 | 4 | 5 | 8 | 11 | 12 | 13 | 14 | 15 | 16 | 20 | 21 | 22 | 24
+    )
+}
+
+fn handshake_name(handshake_type: u8) -> &'static str {
+    match handshake_type {
+        0 => "HelloRequest",
+        1 => "ClientHello",
+        2 => "ServerHello",
+        4 => "NewSessionTicket",
+        5 => "EndOfEarlyData",
+        8 => "EncryptedExtensions",
+        11 => "Certificate",
+        12 => "ServerKeyExchange",
+        13 => "CertificateRequest",
+        14 => "ServerHelloDone",
+        15 => "CertificateVerify",
+        16 => "ClientKeyExchange",
+        20 => "Finished",
+        21 => "CertificateURL",
+        22 => "CertificateStatus",
+        24 => "KeyUpdate",
+        _ => "Unknown Handshake Message",
+    }
+}
+
+fn u16_list(data: &[u8]) -> Vec<u16> {
+    { data.chunks_exact(2) }
+        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+        .collect()
+}
+
+fn parse_extensions(reader: &mut Reader) -> Option<Vec<TlsExtension>> {
+    let mut extensions = Reader::new(reader.vec16()?);
+    let mut parsed = Vec::new();
+    while !extensions.is_empty() {
+        parsed.push(TlsExtension {
+            extension_type: extensions.u16()?,
+            data: extensions.vec16()?.into(),
+        });
+    }
+    Some(parsed)
+}
+
+fn parse_server_name(data: &mut Reader) -> Option<String> {
+    let mut names = Reader::new(data.vec16()?);
+    while !names.is_empty() {
+        let name_type = names.u8()?;
+        let name = names.vec16()?;
+        // 0 is host_name, the only type there is
+        if name_type == 0 {
+            return Some(String::from_utf8_lossy(name).into_owned());
+        }
+    }
+    None
+}
+
+fn parse_alpn(data: &mut Reader) -> Option<Vec<String>> {
+    let mut protocols = Reader::new(data.vec16()?);
+    let mut parsed = Vec::new();
+    while !protocols.is_empty() {
+        parsed.push(String::from_utf8_lossy(protocols.vec8()?).into_owned());
+    }
+    Some(parsed)
+}
+
+fn parse_certificates(body: &[u8]) -> Option<Vec<Certificate>> {
+    let mut reader = Reader::new(body);
+    let mut list = Reader::new(reader.vec24()?);
+    let mut certificates = Vec::new();
+    while !list.is_empty() {
+        if let Some(certificate) = Certificate::from_der(list.vec24()?) {
+            certificates.push(certificate);
+        }
+    }
+    Some(certificates)
+}
+
+impl Certificate {
+    // pulls the subject, issuer and validity out of a DER encoded X.509 certificate
+    fn from_der(der: &[u8]) -> Option<Certificate> {
+        let (_, certificate, _) = der_element(der)?;
+        let (_, tbs, _) = der_element(certificate)?;
+        // the version is an optional explicit [0] in front of the serial number
+        let (tag, _, rest) = der_element(tbs)?;
+        let rest = match tag {
+            0xa0 => der_element(rest)?.2,
+            _ => rest,
+        };
+        // then the signature algorithm
+        let (_, _, rest) = der_element(rest)?;
+        let (_, issuer, rest) = der_element(rest)?;
+        let (_, validity, rest) = der_element(rest)?;
+        let (_, subject, _) = der_element(rest)?;
+
+        let (not_before_tag, not_before, rest) = der_element(validity)?;
+        let (not_after_tag, not_after, _) = der_element(rest)?;
+
+        Some(Certificate {
+            subject: format_name(subject),
+            issuer: format_name(issuer),
+            validity_period: ValidityPeriod {
+                not_before: parse_time(not_before_tag, not_before)?,
+                not_after: parse_time(not_after_tag, not_after)?,
+            },
+        })
+    }
+}
+
+// (tag, content, rest) of the DER element at the front of `data`
+fn der_element(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let [tag, length, rest @ ..] = data else {
+        return None;
+    };
+    let (length, rest) = match *length {
+        length if length < 0x80 => (length as usize, rest),
+        length => {
+            let octets = (length & 0x7f) as usize;
+            if octets > 4 {
+                return None;
+            }
+            let bytes = rest.get(..octets)?;
+            let length = { bytes.iter() }.fold(0usize, |acc, b| acc << 8 | *b as usize);
+            (length, &rest[octets..])
+        }
+    };
+    let content = rest.get(..length)?;
+    Some((*tag, content, &rest[length..]))
+}
+
+// an X.509 Name, as "CN=example.com, O=Example"
+fn format_name(mut name: &[u8]) -> String {
+    let mut parts = Vec::new();
+    while let Some((_, set, rest)) = der_element(name) {
+        name = rest;
+        let Some((_, attribute, _)) = der_element(set) else {
+            continue;
+        };
+        let Some((_, oid, rest)) = der_element(attribute) else {
+            continue;
+        };
+        let Some((_, value, _)) = der_element(rest) else {
+            continue;
+        };
+        let key = match oid {
+            [0x55, 0x04, 0x03] => "CN",
+            [0x55, 0x04, 0x06] => "C",
+            [0x55, 0x04, 0x07] => "L",
+            [0x55, 0x04, 0x08] => "ST",
+            [0x55, 0x04, 0x0a] => "O",
+            [0x55, 0x04, 0x0b] => "OU",
+            [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x01] => "emailAddress",
+            _ => "OID",
+        };
+        parts.push(format!("{key}={}", String::from_utf8_lossy(value)));
+    }
+    parts.join(", ")
+}
+
+fn parse_time(tag: u8, time: &[u8]) -> Option<DateTime<Utc>> {
+    let time = std::str::from_utf8(time).ok()?;
+    let time = match tag {
+        // UTCTime only has two digit years, 50 and up are 19xx
+        0x17 => {
+            let year = time.get(..2)?.parse::<u32>().ok()?;
+            let century = if year >= 50 { "19" } else { "20" };
+            format!("{century}{time}")
+        }
+        0x18 => time.to_owned(),
+        _ => return None,
+    };
+    let time = NaiveDateTime::parse_from_str(time.trim_end_matches('Z'), "%Y%m%d%H%M%S").ok()?;
+    Some(time.and_utc())
+}
+
+// GREASE values look like 0x0a0a, 0x1a1a, ... and are sent to keep servers honest
+pub(crate) fn is_grease(value: u16) -> bool {
+    value & 0x0f0f == 0x0a0a && value >> 8 == value & 0xff
+}
+
+fn format_version(version: u16) -> String {
+    match TlsVersion::from_u16(version) {
+        Some(tls_version) => format!("{tls_version} ({version:#06x})"),
+        None if is_grease(version) => format!("Reserved (GREASE) ({version:#06x})"),
+        None => format!("Unknown ({version:#06x})"),
+    }
+}
+
+fn format_extensions(extensions: &[TlsExtension]) -> String {
+    { extensions.iter() }
+        .map(TlsExtension::name)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn format_optional<T: fmt::Display>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "none".to_owned(),
+    }
+}
+
+fn cipher_suite_name(id: u16) -> Option<&'static str> {
+    let name = match id {
+        0x000a => "TLS_RSA_WITH_3DES_EDE_CBC_SHA",
+        0x002f => "TLS_RSA_WITH_AES_128_CBC_SHA",
+        0x0033 => "TLS_DHE_RSA_WITH_AES_128_CBC_SHA",
+        0x0035 => "TLS_RSA_WITH_AES_256_CBC_SHA",
+        0x0039 => "TLS_DHE_RSA_WITH_AES_256_CBC_SHA",
+        0x003c => "TLS_RSA_WITH_AES_128_CBC_SHA256",
+        0x003d => "TLS_RSA_WITH_AES_256_CBC_SHA256",
+        0x0067 => "TLS_DHE_RSA_WITH_AES_128_CBC_SHA256",
+        0x006b => "TLS_DHE_RSA_WITH_AES_256_CBC_SHA256",
+        0x009c => "TLS_RSA_WITH_AES_128_GCM_SHA256",
+        0x009d => "TLS_RSA_WITH_AES_256_GCM_SHA384",
+        0x009e => "TLS_DHE_RSA_WITH_AES_128_GCM_SHA256",
+        0x009f => "TLS_DHE_RSA_WITH_AES_256_GCM_SHA384",
+        0x00ff => "TLS_EMPTY_RENEGOTIATION_INFO_SCSV",
+        0x1301 => "TLS_AES_128_GCM_SHA256",
+        0x1302 => "TLS_AES_256_GCM_SHA384",
+        0x1303 => "TLS_CHACHA20_POLY1305_SHA256",
+        0x1304 => "TLS_AES_128_CCM_SHA256",
+        0x1305 => "TLS_AES_128_CCM_8_SHA256",
+        0x5600 => "TLS_FALLBACK_SCSV",
+        0xc009 => "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA",
+        0xc00a => "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA",
+        0xc013 => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA",
+        0xc014 => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA",
+        0xc023 => "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256",
+        0xc024 => "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384",
+        0xc027 => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256",
+        0xc028 => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384",
+        0xc02b => "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256",
+        0xc02c => "TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384",
+        0xc02f => "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256",
+        0xc030 => "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+        0xcca8 => "TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+        0xcca9 => "TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256",
+        0xccaa => "TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// reads the big endian, length prefixed fields TLS is made of
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(..length)?;
+        self.data = &self.data[length..];
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u24(&mut self) -> Option<usize> {
+        self.bytes(3)
+            .map(|b| u32::from_be_bytes([0, b[0], b[1], b[2]]) as usize)
+    }
+
+    fn vec8(&mut self) -> Option<&'a [u8]> {
+        let length = self.u8()? as usize;
+        self.bytes(length)
+    }
+
+    fn vec16(&mut self) -> Option<&'a [u8]> {
+        let length = self.u16()? as usize;
+        self.bytes(length)
+    }
+
+    fn vec24(&mut self) -> Option<&'a [u8]> {
+        let length = self.u24()?;
+        self.bytes(length)
+    }
+}
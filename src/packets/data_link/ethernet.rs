@@ -1,72 +1,114 @@
-use crate::packets::packet_traits::{Describable, Layer};
-use crate::packets::shared_objs::Protocol;
-use crate::packets::{
-    internet::ip::Ipv4Packet,
-    shared_objs::{Description, LayerData, Network},
-};
-use chrono::Utc;
+use crate::packets::fields::{Field, FieldValue};
+use crate::packets::internet::net_parser::parse_network_layer;
+use crate::packets::packet_traits::Layer;
+use crate::packets::shared_objs::{LayerData, Network, Protocol};
+use crate::packets::tree::TreeNode;
 use pnet::packet::ethernet::{EtherType, EtherTypes, EthernetPacket};
 use pnet::packet::Packet;
 use std::borrow::Cow;
-use std::collections::BTreeMap;
 
 #[derive(Clone, Debug)]
 pub struct EthernetHeader {
     pub source_mac: Box<str>,
     pub destination_mac: Box<str>,
+    // the type of the payload, after any VLAN tags
     pub ether_type: EtherType,
+    // outermost tag first
+    pub vlan_tags: Box<[VlanTag]>,
+}
+
+// an 802.1Q tag, or an 802.1ad service tag when stacked
+#[derive(Clone, Debug)]
+pub struct VlanTag {
+    pub tpid: EtherType,
+    pub priority: u8,
+    pub drop_eligible: bool,
+    pub vlan_id: u16,
+}
+
+impl VlanTag {
+    fn description(&self) -> String {
+        format!(
+            "id: {}, priority: {}, drop eligible: {}, tpid: {}",
+            self.vlan_id, self.priority, self.drop_eligible, self.tpid
+        )
+    }
 }
 
 #[derive(Debug)]
 pub struct EthernetFrame {
-    pub id: i32,
-    pub timestamp: Box<str>,
     pub header: EthernetHeader,
     pub payload: Network,
 }
 
 impl EthernetFrame {
-    pub fn new(id: i32, packet: &pcap::Packet) -> Option<Self> {
-        let packet = EthernetPacket::new(packet.data)?;
+    pub fn new(data: &[u8]) -> Option<Self> {
+        let packet = EthernetPacket::new(data)?;
+
+        let mut ether_type = packet.get_ethertype();
+        let mut data = packet.payload();
+        let mut vlan_tags = Vec::new();
+        while matches!(
+            ether_type,
+            EtherTypes::Vlan | EtherTypes::PBridge | EtherTypes::QinQ
+        ) {
+            let [tci_hi, tci_lo, type_hi, type_lo, rest @ ..] = data else {
+                break;
+            };
+            let tci = u16::from_be_bytes([*tci_hi, *tci_lo]);
+            vlan_tags.push(VlanTag {
+                tpid: ether_type,
+                priority: (tci >> 13) as u8,
+                drop_eligible: tci & 0x1000 != 0,
+                vlan_id: tci & 0x0FFF,
+            });
+            ether_type = EtherType(u16::from_be_bytes([*type_hi, *type_lo]));
+            data = rest;
+        }
 
         let header = EthernetHeader {
             source_mac: packet.get_source().to_string().into_boxed_str(),
             destination_mac: packet.get_destination().to_string().into_boxed_str(),
-            ether_type: packet.get_ethertype(),
+            ether_type,
+            vlan_tags: vlan_tags.into_boxed_slice(),
         };
 
-        let payload = match header.ether_type {
-            EtherTypes::Ipv4 => Ipv4Packet::new(packet.payload()).map(Network::IPv4),
-            // EtherTypes::Ipv6 => Ipv6Packet::new(packet.payload()).map(|x| Box::new(x) as _),
-            _ => None,
+        let payload = parse_network_layer(header.ether_type, data);
+
+        Some(EthernetFrame { header, payload })
+    }
+
+    // stacked tags read outer to inner, e.g. "100/20"
+    pub fn vlan_ids(&self) -> Option<String> {
+        if self.header.vlan_tags.is_empty() {
+            return None;
         }
-        .unwrap_or_else(|| Network::Other(packet.payload().to_vec().into_boxed_slice()));
-
-        Some(EthernetFrame {
-            id,
-            timestamp: Utc::now().to_string().into_boxed_str(),
-            header,
-            payload,
-        })
+        let ids = { self.header.vlan_tags.iter() }
+            .map(|tag| tag.vlan_id.to_string())
+            .collect::<Vec<String>>();
+        Some(ids.join("/"))
     }
 }
 
 //trait impls
 impl Layer for EthernetFrame {
     fn get_summary(&self) -> String {
+        let vlan_string = { self.header.vlan_tags.iter() }
+            .map(VlanTag::description)
+            .collect::<Vec<String>>()
+            .join("\n");
+
         format!(
             "Source Mac: {}
 Destination Mac: {}
-EtherType: {}",
+EtherType: {}
+VLAN tags: {vlan_string}",
             self.header.source_mac, self.header.destination_mac, self.header.ether_type,
         )
     }
 
-    fn get_next(&self) -> LayerData {
-        match &self.payload {
-            Network::IPv4(x) => LayerData::Layer(x as _),
-            Network::Other(x) => LayerData::Data(x),
-        }
+    fn get_next(&self) -> LayerData<'_> {
+        self.payload.get_layer_data()
     }
 
     fn protocol(&self) -> Protocol {
@@ -78,71 +120,73 @@ EtherType: {}",
     }
 
     fn destination(&self) -> Cow<'_, str> {
-        Cow::from(self.header.source_mac.to_string())
+        Cow::from(self.header.destination_mac.to_string())
     }
 
     fn info(&self) -> String {
-        format!("next header {}", self.header.ether_type)
-    }
-}
-
-impl Describable for EthernetFrame {
-    fn get_long(&self) -> BTreeMap<Protocol, String> {
-        let mut map = BTreeMap::new();
-        map.insert(self.protocol(), self.get_summary());
-        let mut layer_data = self.get_next();
-        while let LayerData::Layer(layer) = layer_data {
-            map.insert(layer.protocol(), layer.get_summary());
-            layer_data = layer.get_next();
+        match self.vlan_ids() {
+            Some(vlan) => format!("VLAN {vlan}, next header {}", self.header.ether_type),
+            None => format!("next header {}", self.header.ether_type),
         }
-        match layer_data {
-            LayerData::Layer(_) => {}
-            LayerData::Application(app) => {
-                map.insert(app.protocol(), app.get_summary());
-            }
-            LayerData::Data(_) => {}
-        }
-        map
-    }
-
-    fn get_id(&self) -> i32 {
-        self.id
     }
 
-    fn get_description(&self) -> Description<'_> {
-        let next_else_self: &dyn Layer = match self.get_next() {
-            LayerData::Layer(x) => x,
-            _ => self as _,
-        };
-
-        let innermost_layer: LayerData<'_> = get_innermost_layer(LayerData::Layer(self));
-
-        Description {
-            id: self.id,
-            timestamp: &self.timestamp,
-            src_dest_layer: next_else_self,
-            info_layer: innermost_layer,
+    // the vlan tags are reported here too, they're part of the ethernet header
+    fn fields(&self) -> Vec<Field> {
+        let source = FieldValue::mac(&self.header.source_mac);
+        let destination = FieldValue::mac(&self.header.destination_mac);
+        let mut fields = vec![];
+        fields.extend(source.clone().map(|mac| Field::new("eth.src", mac)));
+        fields.extend(destination.clone().map(|mac| Field::new("eth.dst", mac)));
+        fields.extend(source.map(|mac| Field::new("eth.addr", mac)));
+        fields.extend(destination.map(|mac| Field::new("eth.addr", mac)));
+        fields.push(Field::new("eth.type", self.header.ether_type.0));
+        for tag in self.header.vlan_tags.iter() {
+            fields.push(Field::new("vlan.id", tag.vlan_id));
+            fields.push(Field::new("vlan.priority", tag.priority));
+            fields.push(Field::new("vlan.dei", tag.drop_eligible));
         }
+        fields
     }
-}
 
-// helper function
-fn get_innermost_layer<'a>(mut layer: LayerData<'a>) -> LayerData<'a> {
-    let mut last_layer: Option<&'a dyn Layer> = None;
-
-    while let LayerData::Layer(current_layer) = layer {
-        last_layer = Some(current_layer);
-        layer = current_layer.get_next();
+    fn tree(&self) -> TreeNode {
+        let header = &self.header;
+        let mut children = vec![
+            TreeNode::at(format!("Destination Mac: {}", header.destination_mac), 0, 6),
+            TreeNode::at(format!("Source Mac: {}", header.source_mac), 6, 6),
+        ];
+        for (i, tag) in header.vlan_tags.iter().enumerate() {
+            // each tag sits where the ethertype would, the tpid being that ethertype
+            let start = 12 + 4 * i;
+            children.push(
+                TreeNode::at(format!("VLAN tag: {}", tag.description()), start, 4).with_children(
+                    vec![
+                        TreeNode::at(format!("tpid: {}", tag.tpid), start, 2),
+                        TreeNode::at(format!("priority: {}", tag.priority), start + 2, 1),
+                        TreeNode::at(
+                            format!("drop eligible: {}", tag.drop_eligible),
+                            start + 2,
+                            1,
+                        ),
+                        TreeNode::at(format!("id: {}", tag.vlan_id), start + 2, 2),
+                    ],
+                ),
+            );
+        }
+        let ether_type = 12 + 4 * header.vlan_tags.len();
+        children.push(TreeNode::at(
+            format!("EtherType: {}", header.ether_type),
+            ether_type,
+            2,
+        ));
+
+        let label = format!(
+            "Ethernet, Src: {}, Dst: {}",
+            header.source_mac, header.destination_mac
+        );
+        TreeNode::at(label, 0, ether_type + 2).with_children(children)
     }
 
-    match layer {
-        LayerData::Data(_) => {
-            if let Some(last) = last_layer {
-                LayerData::Layer(last)
-            } else {
-                LayerData::Data(&[])
-            }
-        }
-        _ => layer,
+    fn header_len(&self) -> Option<usize> {
+        Some(14 + 4 * self.header.vlan_tags.len())
     }
 }
@@ -0,0 +1,102 @@
+use crate::packets::fields::Field;
+use crate::packets::internet::net_parser::parse_network_layer;
+use crate::packets::packet_traits::Layer;
+use crate::packets::shared_objs::{LayerData, Network, Protocol};
+use crate::packets::tree::TreeNode;
+use pnet::packet::ethernet::EtherTypes;
+use std::borrow::Cow;
+
+// BSD loopback encapsulation, a 4 byte address family in front of the packet.
+// LINKTYPE_NULL stores it in the byte order of the capturing host,
+// LINKTYPE_LOOP always in network byte order.
+// https://www.tcpdump.org/linktypes/LINKTYPE_NULL.html
+
+#[derive(Debug)]
+pub struct LoopbackPacket {
+    pub family: u32,
+    pub payload: Network,
+}
+
+impl LoopbackPacket {
+    pub fn new_null(data: &[u8]) -> Option<Self> {
+        let family = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+        // the writer's byte order is unknown, but families are small numbers so
+        // anything in the upper half means it was big endian
+        let family = if family & 0xFFFF_0000 != 0 {
+            family.swap_bytes()
+        } else {
+            family
+        };
+        Some(Self::with_family(family, &data[4..]))
+    }
+
+    pub fn new_loop(data: &[u8]) -> Option<Self> {
+        let family = u32::from_be_bytes(data.get(..4)?.try_into().ok()?);
+        Some(Self::with_family(family, &data[4..]))
+    }
+
+    fn with_family(family: u32, payload: &[u8]) -> Self {
+        let ether_type = match family {
+            2 => EtherTypes::Ipv4,
+            // linux, then the BSDs and darwin all picked their own AF_INET6
+            10 | 24 | 28 | 30 => EtherTypes::Ipv6,
+            _ => {
+                return LoopbackPacket {
+                    family,
+                    payload: Network::Other(payload.to_vec().into_boxed_slice()),
+                }
+            }
+        };
+
+        LoopbackPacket {
+            family,
+            payload: parse_network_layer(ether_type, payload),
+        }
+    }
+
+    fn family_name(&self) -> &'static str {
+        match self.family {
+            2 => "AF_INET",
+            10 | 24 | 28 | 30 => "AF_INET6",
+            _ => "unknown",
+        }
+    }
+}
+
+impl Layer for LoopbackPacket {
+    fn get_summary(&self) -> String {
+        format!("family: {} ({})", self.family_name(), self.family)
+    }
+
+    fn get_next(&self) -> LayerData<'_> {
+        self.payload.get_layer_data()
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::Loopback
+    }
+
+    fn source(&self) -> Cow<'_, str> {
+        Cow::from("loopback")
+    }
+
+    fn destination(&self) -> Cow<'_, str> {
+        Cow::from("loopback")
+    }
+
+    fn info(&self) -> String {
+        format!("Loopback, family {}", self.family_name())
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        vec![Field::new("null.family", self.family)]
+    }
+
+    fn tree(&self) -> TreeNode {
+        TreeNode::at("Loopback", 0, 4).with_children(vec![TreeNode::at(self.get_summary(), 0, 4)])
+    }
+
+    fn header_len(&self) -> Option<usize> {
+        Some(4)
+    }
+}
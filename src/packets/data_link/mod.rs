@@ -1 +1,3 @@
 pub mod ethernet;
+pub mod loopback;
+pub mod sll;
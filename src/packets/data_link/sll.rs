@@ -0,0 +1,272 @@
+use crate::packets::fields::Field;
+use crate::packets::internet::net_parser::parse_network_layer;
+use crate::packets::packet_traits::Layer;
+use crate::packets::shared_objs::{LayerData, Network, Protocol};
+use crate::packets::tree::TreeNode;
+use pnet::packet::ethernet::EtherType;
+use pnet::packet::sll::SLLPacket;
+use pnet::packet::sll2::SLL2Packet;
+use pnet::packet::Packet;
+use std::borrow::Cow;
+
+// Linux "cooked" captures, what you get when capturing on the `any` device.
+// https://www.tcpdump.org/linktypes/LINKTYPE_LINUX_SLL.html
+// https://www.tcpdump.org/linktypes/LINKTYPE_LINUX_SLL2.html
+
+#[derive(Clone, Debug)]
+pub struct SllHeader {
+    pub packet_type: u16,
+    pub arphrd_type: u16,
+    pub address: Box<str>,
+    pub protocol: EtherType,
+}
+
+#[derive(Debug)]
+pub struct SllPacket {
+    pub header: SllHeader,
+    pub payload: Network,
+}
+
+#[derive(Clone, Debug)]
+pub struct Sll2Header {
+    pub protocol: EtherType,
+    pub interface_index: u32,
+    pub arphrd_type: u16,
+    pub packet_type: u8,
+    pub address: Box<str>,
+}
+
+#[derive(Debug)]
+pub struct Sll2Packet {
+    pub header: Sll2Header,
+    pub payload: Network,
+}
+
+impl SllPacket {
+    pub fn new(data: &[u8]) -> Option<Self> {
+        let packet = SLLPacket::new(data)?;
+
+        let header = SllHeader {
+            packet_type: packet.get_packet_type(),
+            arphrd_type: packet.get_link_layer_address_type(),
+            address: format_address(
+                &packet.get_link_layer_address(),
+                packet.get_link_layer_address_len() as usize,
+            ),
+            protocol: packet.get_protocol(),
+        };
+
+        let payload = parse_network_layer(header.protocol, packet.payload());
+
+        Some(SllPacket { header, payload })
+    }
+}
+
+impl Sll2Packet {
+    pub fn new(data: &[u8]) -> Option<Self> {
+        let packet = SLL2Packet::new(data)?;
+
+        let header = Sll2Header {
+            protocol: packet.get_protocol_type(),
+            interface_index: packet.get_interface_index(),
+            arphrd_type: packet.get_arphrd_type(),
+            packet_type: packet.get_packet_type(),
+            address: format_address(
+                &packet.get_link_layer_address(),
+                packet.get_link_layer_address_length() as usize,
+            ),
+        };
+
+        let payload = parse_network_layer(header.protocol, packet.payload());
+
+        Some(Sll2Packet { header, payload })
+    }
+}
+
+//trait impls
+impl Layer for SllPacket {
+    fn get_summary(&self) -> String {
+        let SllHeader {
+            packet_type,
+            arphrd_type,
+            address,
+            protocol,
+        } = &self.header;
+
+        format!(
+            "packet_type: {} ({packet_type})
+arphrd_type: {arphrd_type}
+address: {address}
+protocol: {protocol}",
+            packet_type_name(*packet_type)
+        )
+    }
+
+    fn get_next(&self) -> LayerData<'_> {
+        self.payload.get_layer_data()
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::LinuxSll
+    }
+
+    fn source(&self) -> Cow<'_, str> {
+        Cow::from(self.header.address.to_string())
+    }
+
+    fn destination(&self) -> Cow<'_, str> {
+        Cow::from("na")
+    }
+
+    fn info(&self) -> String {
+        format!(
+            "{}, next header {}",
+            packet_type_name(self.header.packet_type),
+            self.header.protocol
+        )
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            Field::new("sll.pkttype", self.header.packet_type),
+            Field::new("sll.hatype", self.header.arphrd_type),
+            Field::new("sll.etype", self.header.protocol.0),
+        ]
+    }
+
+    fn tree(&self) -> TreeNode {
+        let SllHeader {
+            packet_type,
+            arphrd_type,
+            address,
+            protocol,
+        } = &self.header;
+
+        let packet_type_name = packet_type_name(*packet_type);
+        TreeNode::at(self.protocol().to_string(), 0, 16).with_children(vec![
+            TreeNode::at(
+                format!("packet_type: {packet_type_name} ({packet_type})"),
+                0,
+                2,
+            ),
+            TreeNode::at(format!("arphrd_type: {arphrd_type}"), 2, 2),
+            TreeNode::at(format!("address: {address}"), 4, 10),
+            TreeNode::at(format!("protocol: {protocol}"), 14, 2),
+        ])
+    }
+
+    fn header_len(&self) -> Option<usize> {
+        Some(16)
+    }
+}
+
+impl Layer for Sll2Packet {
+    fn get_summary(&self) -> String {
+        let Sll2Header {
+            protocol,
+            interface_index,
+            arphrd_type,
+            packet_type,
+            address,
+        } = &self.header;
+
+        format!(
+            "protocol: {protocol}
+interface_index: {interface_index}
+arphrd_type: {arphrd_type}
+packet_type: {} ({packet_type})
+address: {address}",
+            packet_type_name(*packet_type as u16)
+        )
+    }
+
+    fn get_next(&self) -> LayerData<'_> {
+        self.payload.get_layer_data()
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::LinuxSll2
+    }
+
+    fn source(&self) -> Cow<'_, str> {
+        Cow::from(self.header.address.to_string())
+    }
+
+    fn destination(&self) -> Cow<'_, str> {
+        Cow::from("na")
+    }
+
+    fn info(&self) -> String {
+        format!(
+            "{} on interface {}, next header {}",
+            packet_type_name(self.header.packet_type as u16),
+            self.header.interface_index,
+            self.header.protocol
+        )
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            Field::new("sll.pkttype", self.header.packet_type),
+            Field::new("sll.hatype", self.header.arphrd_type),
+            Field::new("sll.etype", self.header.protocol.0),
+            Field::new("sll.ifindex", self.header.interface_index),
+        ]
+    }
+
+    fn tree(&self) -> TreeNode {
+        let Sll2Header {
+            protocol,
+            interface_index,
+            arphrd_type,
+            packet_type,
+            address,
+        } = &self.header;
+
+        let packet_type_name = packet_type_name(*packet_type as u16);
+        TreeNode::at(self.protocol().to_string(), 0, 20).with_children(vec![
+            TreeNode::at(format!("protocol: {protocol}"), 0, 2),
+            TreeNode::at(format!("interface_index: {interface_index}"), 4, 4),
+            TreeNode::at(format!("arphrd_type: {arphrd_type}"), 8, 2),
+            TreeNode::at(
+                format!("packet_type: {packet_type_name} ({packet_type})"),
+                10,
+                1,
+            ),
+            TreeNode::at(format!("address: {address}"), 11, 9),
+        ])
+    }
+
+    fn header_len(&self) -> Option<usize> {
+        Some(20)
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+fn packet_type_name(packet_type: u16) -> &'static str {
+    match packet_type {
+        0 => "unicast to us",
+        1 => "broadcast",
+        2 => "multicast",
+        3 => "unicast to another host",
+        4 => "sent by us",
+        _ => "unknown",
+    }
+}
+
+// the address field is always 8 bytes, only the first `len` of them are used
+fn format_address(address: &[u8], len: usize) -> Box<str> {
+    address[..len.min(address.len())]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
+        .into_boxed_str()
+}
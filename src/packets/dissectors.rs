@@ -0,0 +1,112 @@
+// Dissectors from outside this crate. Application protocols go in the same
+// registry as the built in ones (see `AppDissector`), anything the built in
+// parsers don't know below that can be claimed by its ethertype or ip protocol
+// number. Register before starting a capture, packets already dissected keep
+// what they were parsed as.
+use crate::packets::application::registry;
+use crate::packets::fields::FieldKind;
+use crate::packets::packet_traits::Layer;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+pub use crate::packets::application::app_parser::{StreamContext, StreamParse};
+pub use crate::packets::application::registry::{
+    clear_decode_as, decode_as, decode_as_rules, dissector_names, AppDissector, TransportProtocol,
+};
+
+// Turns the payload after an ethertype or ip protocol number into a layer. The
+// layer's `protocol` should be `Protocol::Custom` with the protocol's name.
+pub trait LayerDissector: Send + Sync {
+    // the protocol's name, display filters know the protocol by it
+    fn name(&self) -> &str;
+
+    fn dissect(&self, data: &[u8]) -> Option<Box<dyn Layer>>;
+
+    // same as `AppDissector::fields`
+    fn fields(&self) -> &[(&'static str, FieldKind)] {
+        &[]
+    }
+}
+
+// Fails when there is already a dissector with the same name
+pub fn register_app_dissector(dissector: impl AppDissector + 'static) -> Result<(), String> {
+    registry::register(Arc::new(dissector))
+}
+
+// Network layer protocols, e.g. ethertype 0x88b5. Replaces any earlier
+// dissector for the same ethertype. Built in ones can't be overridden.
+pub fn register_ether_type(ether_type: u16, dissector: impl LayerDissector + 'static) {
+    write(&ETHER_TYPES).insert(ether_type, Arc::new(dissector));
+}
+
+// Transport layer protocols, e.g. ip protocol 253. Same rules as ethertypes.
+pub fn register_ip_protocol(protocol: u8, dissector: impl LayerDissector + 'static) {
+    write(&IP_PROTOCOLS).insert(protocol, Arc::new(dissector));
+}
+
+// every registered protocol, application ones included
+pub fn protocol_names() -> Vec<String> {
+    let layers = { read(&ETHER_TYPES).values() }
+        .chain(read(&IP_PROTOCOLS).values())
+        .map(|dissector| dissector.name().to_owned())
+        .collect::<Vec<String>>();
+    let mut names = dissector_names();
+    names.extend(layers);
+    names.sort();
+    names.dedup();
+    names
+}
+
+// the fields every registered dissector says its layers report
+pub(crate) fn declared_fields() -> Vec<(&'static str, FieldKind)> {
+    let mut fields = { registry::dissectors().iter() }
+        .flat_map(|dissector| dissector.fields().to_vec())
+        .collect::<Vec<_>>();
+    for dissector in { read(&ETHER_TYPES).values() }.chain(read(&IP_PROTOCOLS).values()) {
+        fields.extend_from_slice(dissector.fields());
+    }
+    fields
+}
+
+pub(crate) fn dissect_ether_type(ether_type: u16, data: &[u8]) -> Option<Box<dyn Layer>> {
+    dissect(&ETHER_TYPES, ether_type, data)
+}
+
+pub(crate) fn dissect_ip_protocol(protocol: u8, data: &[u8]) -> Option<Box<dyn Layer>> {
+    dissect(&IP_PROTOCOLS, protocol, data)
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+type LayerDissectors<K> = LazyLock<RwLock<HashMap<K, Arc<dyn LayerDissector>>>>;
+
+static ETHER_TYPES: LayerDissectors<u16> = LazyLock::new(Default::default);
+static IP_PROTOCOLS: LayerDissectors<u8> = LazyLock::new(Default::default);
+
+fn write<K>(
+    dissectors: &'static LayerDissectors<K>,
+) -> std::sync::RwLockWriteGuard<'static, HashMap<K, Arc<dyn LayerDissector>>> {
+    dissectors.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn read<K>(
+    dissectors: &'static LayerDissectors<K>,
+) -> std::sync::RwLockReadGuard<'static, HashMap<K, Arc<dyn LayerDissector>>> {
+    dissectors.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn dissect<K: std::hash::Hash + Eq>(
+    dissectors: &'static LayerDissectors<K>,
+    key: K,
+    data: &[u8],
+) -> Option<Box<dyn Layer>> {
+    // cloned out so a dissector can parse nested layers without deadlocking
+    let dissector = { read(dissectors) }.get(&key).cloned()?;
+    dissector.dissect(data)
+}
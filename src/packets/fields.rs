@@ -0,0 +1,326 @@
+// Named, typed values a layer reports about itself through `Layer::fields` and
+// `AppLayer::fields`, e.g. "tcp.dstport" = 443. Names follow wireshark's display
+// filter names and always start with the protocol's filter name
+// (`Protocol::filter_name`). A field can occur more than once in a packet,
+// ip.addr holds both addresses.
+
+use crate::packets::dissectors;
+use std::fmt;
+use std::net::IpAddr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub value: FieldValue,
+}
+
+impl Field {
+    pub fn new(name: &'static str, value: impl Into<FieldValue>) -> Field {
+        Field {
+            name,
+            value: value.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Int,
+    Bool,
+    Str,
+    Bytes,
+    Ip,
+    Mac,
+}
+
+impl fmt::Display for FieldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldKind::Int => write!(f, "an integer"),
+            FieldKind::Bool => write!(f, "a boolean"),
+            FieldKind::Str => write!(f, "a string"),
+            FieldKind::Bytes => write!(f, "a byte sequence"),
+            FieldKind::Ip => write!(f, "an IP address"),
+            FieldKind::Mac => write!(f, "a MAC address"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    Int(u64),
+    Bool(bool),
+    Str(String),
+    Bytes(Vec<u8>),
+    Ip(IpAddr),
+    Mac([u8; 6]),
+}
+
+impl FieldValue {
+    pub fn kind(&self) -> FieldKind {
+        match self {
+            FieldValue::Int(_) => FieldKind::Int,
+            FieldValue::Bool(_) => FieldKind::Bool,
+            FieldValue::Str(_) => FieldKind::Str,
+            FieldValue::Bytes(_) => FieldKind::Bytes,
+            FieldValue::Ip(_) => FieldKind::Ip,
+            FieldValue::Mac(_) => FieldKind::Mac,
+        }
+    }
+
+    // addresses are stored as text in the layers
+    pub fn ip(address: &str) -> Option<FieldValue> {
+        address.parse().ok().map(FieldValue::Ip)
+    }
+
+    // aa:bb:cc:dd:ee:ff, with '-' or '.' also taken as separators
+    pub fn mac(address: &str) -> Option<FieldValue> {
+        parse_hex_bytes(address)?
+            .try_into()
+            .ok()
+            .map(FieldValue::Mac)
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Int(value) => write!(f, "{value}"),
+            FieldValue::Bool(value) => write!(f, "{value}"),
+            FieldValue::Str(value) => write!(f, "{value}"),
+            FieldValue::Bytes(bytes) => write!(f, "{}", hex_bytes(bytes)),
+            FieldValue::Ip(address) => write!(f, "{address}"),
+            FieldValue::Mac(mac) => write!(f, "{}", hex_bytes(mac)),
+        }
+    }
+}
+
+impl From<u8> for FieldValue {
+    fn from(value: u8) -> Self {
+        FieldValue::Int(value.into())
+    }
+}
+
+impl From<u16> for FieldValue {
+    fn from(value: u16) -> Self {
+        FieldValue::Int(value.into())
+    }
+}
+
+impl From<u32> for FieldValue {
+    fn from(value: u32) -> Self {
+        FieldValue::Int(value.into())
+    }
+}
+
+impl From<u64> for FieldValue {
+    fn from(value: u64) -> Self {
+        FieldValue::Int(value)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Bool(value)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::Str(value.to_owned())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::Str(value)
+    }
+}
+
+impl From<IpAddr> for FieldValue {
+    fn from(value: IpAddr) -> Self {
+        FieldValue::Ip(value)
+    }
+}
+
+// The kind of a built in field or one declared by a registered dissector
+pub fn field_kind(name: &str) -> Option<FieldKind> {
+    { BUILT_IN.iter().copied() }
+        .chain(dissectors::declared_fields())
+        .find(|(field, _)| *field == name)
+        .map(|(_, kind)| kind)
+}
+
+// every field a layer can report, built in ones first
+pub fn known_fields() -> Vec<(&'static str, FieldKind)> {
+    let mut fields = BUILT_IN.to_vec();
+    fields.extend(dissectors::declared_fields());
+    fields
+}
+
+pub(crate) fn hex_bytes(bytes: &[u8]) -> String {
+    { bytes.iter() }
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<String>>()
+        .join(":")
+}
+
+// aa:bb:cc, aa-bb-cc, aa.bb.cc or aabbcc
+pub(crate) fn parse_hex_bytes(text: &str) -> Option<Vec<u8>> {
+    let pairs = match text.contains([':', '-', '.']) {
+        true => text.split([':', '-', '.']).collect::<Vec<&str>>(),
+        false if text.len().is_multiple_of(2) => { (0..text.len()).step_by(2) }
+            .map(|i| text.get(i..i + 2))
+            .collect::<Option<Vec<&str>>>()?,
+        false => return None,
+    };
+    { pairs.into_iter() }
+        .map(|pair| match pair.len() {
+            1 | 2 => u8::from_str_radix(pair, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+const BUILT_IN: &[(&str, FieldKind)] = &[
+    ("frame.number", FieldKind::Int),
+    ("frame.len", FieldKind::Int),
+    ("frame.cap_len", FieldKind::Int),
+    ("eth.src", FieldKind::Mac),
+    ("eth.dst", FieldKind::Mac),
+    ("eth.addr", FieldKind::Mac),
+    ("eth.type", FieldKind::Int),
+    ("vlan.id", FieldKind::Int),
+    ("vlan.priority", FieldKind::Int),
+    ("vlan.dei", FieldKind::Bool),
+    ("sll.pkttype", FieldKind::Int),
+    ("sll.hatype", FieldKind::Int),
+    ("sll.etype", FieldKind::Int),
+    ("sll.ifindex", FieldKind::Int),
+    ("null.family", FieldKind::Int),
+    ("arp.opcode", FieldKind::Int),
+    ("arp.hw.type", FieldKind::Int),
+    ("arp.proto.type", FieldKind::Int),
+    ("arp.src.hw_mac", FieldKind::Mac),
+    ("arp.src.proto_ipv4", FieldKind::Ip),
+    ("arp.dst.hw_mac", FieldKind::Mac),
+    ("arp.dst.proto_ipv4", FieldKind::Ip),
+    ("ip.src", FieldKind::Ip),
+    ("ip.dst", FieldKind::Ip),
+    ("ip.addr", FieldKind::Ip),
+    ("ip.version", FieldKind::Int),
+    ("ip.dsfield.dscp", FieldKind::Int),
+    ("ip.dsfield.ecn", FieldKind::Int),
+    ("ip.len", FieldKind::Int),
+    ("ip.id", FieldKind::Int),
+    ("ip.flags.df", FieldKind::Bool),
+    ("ip.flags.mf", FieldKind::Bool),
+    ("ip.frag_offset", FieldKind::Int),
+    ("ip.ttl", FieldKind::Int),
+    ("ip.proto", FieldKind::Int),
+    ("ipv6.src", FieldKind::Ip),
+    ("ipv6.dst", FieldKind::Ip),
+    ("ipv6.addr", FieldKind::Ip),
+    ("ipv6.tclass", FieldKind::Int),
+    ("ipv6.flow", FieldKind::Int),
+    ("ipv6.plen", FieldKind::Int),
+    ("ipv6.nxt", FieldKind::Int),
+    ("ipv6.hlim", FieldKind::Int),
+    ("icmp.type", FieldKind::Int),
+    ("icmp.code", FieldKind::Int),
+    ("icmp.ident", FieldKind::Int),
+    ("icmp.seq", FieldKind::Int),
+    ("icmpv6.type", FieldKind::Int),
+    ("icmpv6.code", FieldKind::Int),
+    ("icmpv6.echo.identifier", FieldKind::Int),
+    ("icmpv6.echo.sequence_number", FieldKind::Int),
+    ("tcp.srcport", FieldKind::Int),
+    ("tcp.dstport", FieldKind::Int),
+    ("tcp.port", FieldKind::Int),
+    ("tcp.stream", FieldKind::Int),
+    ("tcp.len", FieldKind::Int),
+    ("tcp.seq", FieldKind::Int),
+    ("tcp.ack", FieldKind::Int),
+    ("tcp.hdr_len", FieldKind::Int),
+    ("tcp.window_size_value", FieldKind::Int),
+    ("tcp.flags.urg", FieldKind::Bool),
+    ("tcp.flags.ack", FieldKind::Bool),
+    ("tcp.flags.push", FieldKind::Bool),
+    ("tcp.flags.reset", FieldKind::Bool),
+    ("tcp.flags.syn", FieldKind::Bool),
+    ("tcp.flags.fin", FieldKind::Bool),
+    ("tcp.analysis.flags", FieldKind::Bool),
+    ("tcp.analysis.retransmission", FieldKind::Bool),
+    ("tcp.analysis.out_of_order", FieldKind::Bool),
+    ("tcp.analysis.overlap", FieldKind::Bool),
+    ("tcp.analysis.lost_segment", FieldKind::Bool),
+    ("udp.srcport", FieldKind::Int),
+    ("udp.dstport", FieldKind::Int),
+    ("udp.port", FieldKind::Int),
+    ("udp.length", FieldKind::Int),
+    ("dns.id", FieldKind::Int),
+    ("dns.flags.response", FieldKind::Bool),
+    ("dns.flags.opcode", FieldKind::Int),
+    ("dns.flags.authoritative", FieldKind::Bool),
+    ("dns.flags.truncated", FieldKind::Bool),
+    ("dns.flags.recdesired", FieldKind::Bool),
+    ("dns.flags.recavail", FieldKind::Bool),
+    ("dns.flags.rcode", FieldKind::Int),
+    ("dns.count.queries", FieldKind::Int),
+    ("dns.count.answers", FieldKind::Int),
+    ("dns.count.auth_rr", FieldKind::Int),
+    ("dns.count.add_rr", FieldKind::Int),
+    ("dns.qry.name", FieldKind::Str),
+    ("dns.qry.type", FieldKind::Int),
+    ("dns.qry.class", FieldKind::Int),
+    ("dns.resp.name", FieldKind::Str),
+    ("dns.resp.type", FieldKind::Int),
+    ("dns.resp.ttl", FieldKind::Int),
+    ("dns.a", FieldKind::Ip),
+    ("dns.aaaa", FieldKind::Ip),
+    ("dns.cname", FieldKind::Str),
+    ("dns.ns", FieldKind::Str),
+    ("dns.ptr.domain_name", FieldKind::Str),
+    ("http.request", FieldKind::Bool),
+    ("http.response", FieldKind::Bool),
+    ("http.request.method", FieldKind::Str),
+    ("http.request.uri", FieldKind::Str),
+    ("http.request.version", FieldKind::Str),
+    ("http.response.version", FieldKind::Str),
+    ("http.response.code", FieldKind::Int),
+    ("http.response.phrase", FieldKind::Str),
+    ("http.host", FieldKind::Str),
+    ("http.user_agent", FieldKind::Str),
+    ("http.server", FieldKind::Str),
+    ("http.content_type", FieldKind::Str),
+    ("http.content_length", FieldKind::Int),
+    ("http.request_in", FieldKind::Int),
+    ("http.response_in", FieldKind::Int),
+    ("http.file_data", FieldKind::Bytes),
+    ("tls.record.content_type", FieldKind::Int),
+    ("tls.record.version", FieldKind::Int),
+    ("tls.handshake.type", FieldKind::Int),
+    ("tls.handshake.version", FieldKind::Int),
+    ("tls.handshake.ciphersuite", FieldKind::Int),
+    ("tls.handshake.extension.type", FieldKind::Int),
+    ("tls.handshake.extensions_server_name", FieldKind::Str),
+    ("tls.handshake.extensions_alpn_str", FieldKind::Str),
+    ("tls.handshake.extensions.supported_version", FieldKind::Int),
+    ("tls.handshake.ja3", FieldKind::Str),
+    ("tls.handshake.ja3_full", FieldKind::Str),
+    ("tls.handshake.ja3s", FieldKind::Str),
+    ("tls.handshake.ja3s_full", FieldKind::Str),
+    ("tls.handshake.ja4", FieldKind::Str),
+    ("tls.handshake.certificate.subject", FieldKind::Str),
+    ("tls.handshake.certificate.issuer", FieldKind::Str),
+    ("tls.alert_message.level", FieldKind::Int),
+    ("tls.alert_message.desc", FieldKind::Int),
+];
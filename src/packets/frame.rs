@@ -0,0 +1,292 @@
+use crate::packets::data_link::{ethernet::EthernetFrame, loopback::LoopbackPacket, sll};
+use crate::packets::fields::{Field, FieldValue};
+use crate::packets::internet::net_parser::parse_raw_ip;
+use crate::packets::packet_traits::{Describable, Layer};
+use crate::packets::shared_objs::{
+    Application, DataLink, Description, LayerData, Network, Protocol, Transport,
+};
+use crate::packets::tree::TreeNode;
+use chrono::{DateTime, Utc};
+use pcap::Linktype;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+#[derive(Debug)]
+pub struct Frame {
+    pub id: i32,
+    pub timestamp: DateTime<Utc>,
+    pub link_type: Linktype,
+    pub payload: DataLink,
+    // kept so the capture can be written back out untouched
+    pub packet_header: pcap::PacketHeader,
+    pub data: Box<[u8]>,
+}
+
+impl Frame {
+    pub fn new(id: i32, link_type: Linktype, raw_packet: &pcap::Packet) -> Self {
+        let data = raw_packet.data;
+
+        let payload = match link_type {
+            Linktype::ETHERNET => EthernetFrame::new(data).map(DataLink::Ethernet),
+            Linktype::LINUX_SLL => sll::SllPacket::new(data).map(DataLink::LinuxSll),
+            Linktype::LINUX_SLL2 => sll::Sll2Packet::new(data).map(DataLink::LinuxSll2),
+            Linktype::NULL => LoopbackPacket::new_null(data).map(DataLink::Loopback),
+            Linktype::LOOP => LoopbackPacket::new_loop(data).map(DataLink::Loopback),
+            Linktype::RAW | Linktype::IPV4 | Linktype::IPV6 => {
+                Some(DataLink::Raw(parse_raw_ip(data)))
+            }
+            _ => None,
+        }
+        .unwrap_or_else(|| DataLink::Other(data.to_vec().into_boxed_slice()));
+
+        Frame {
+            id,
+            timestamp: capture_time(raw_packet.header),
+            link_type,
+            payload,
+            packet_header: *raw_packet.header,
+            data: data.to_vec().into_boxed_slice(),
+        }
+    }
+
+    pub fn vlan_ids(&self) -> Option<String> {
+        match &self.payload {
+            DataLink::Ethernet(x) => x.vlan_ids(),
+            _ => None,
+        }
+    }
+
+    pub fn link_type_name(&self) -> String {
+        self.link_type
+            .get_name()
+            .unwrap_or_else(|_| format!("linktype {}", self.link_type.0))
+    }
+
+    // the frame itself and every layer decoded under it, outermost first. A TCP
+    // segment that finished more than one message has all of them at the end.
+    pub fn layers(&self) -> Vec<LayerData<'_>> {
+        let mut layers = vec![];
+        let mut current = LayerData::Layer(self as &dyn Layer);
+        loop {
+            let next = match current {
+                LayerData::Layer(layer) => Some(layer.get_next()),
+                LayerData::Application(_) => None,
+                LayerData::Data(_) => break,
+            };
+            layers.push(current);
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        if let Some(Transport::TCP(tcp)) = self.payload.network().and_then(Network::transport) {
+            layers.extend(
+                { tcp.more_messages.iter() }
+                    .map(Application::get_layer_data)
+                    .filter(|layer| !matches!(layer, LayerData::Data(_))),
+            );
+        }
+        layers
+    }
+
+    pub fn all_fields(&self) -> Vec<Field> {
+        { self.layers().iter() }
+            .flat_map(LayerData::fields)
+            .collect()
+    }
+
+    // the drill down of every layer plus any undecoded data after them, with
+    // byte ranges placed in `data`
+    pub fn tree(&self) -> Vec<TreeNode> {
+        let mut layers = self.layers();
+        if let Some(LayerData::Layer(last)) = layers.last() {
+            let next = last.get_next();
+            if matches!(next, LayerData::Data(data) if !data.is_empty()) {
+                layers.push(next);
+            }
+        }
+
+        let mut offset = Some(0);
+        { layers.iter() }
+            .map(|layer| {
+                let mut node = layer.tree();
+                node.place(offset, self.data.len());
+                offset = offset
+                    .zip(layer.header_len())
+                    .map(|(offset, len)| offset + len);
+                node
+            })
+            .collect()
+    }
+
+    // every value of one field, e.g. both ports for "tcp.port". Only the layers
+    // of the protocol the name starts with are asked.
+    pub fn field_values(&self, name: &str) -> Vec<FieldValue> {
+        let protocol = match name.split('.').next().unwrap_or(name) {
+            // the tags are part of the ethernet header
+            "vlan" => "eth",
+            protocol => protocol,
+        };
+        { self.layers().iter() }
+            .filter(|layer| { layer.protocol() }.is_some_and(|p| p.filter_name() == protocol))
+            .flat_map(LayerData::fields)
+            .filter(|field| field.name == name)
+            .map(|field| field.value)
+            .collect()
+    }
+}
+
+//trait impls
+impl Layer for Frame {
+    fn get_summary(&self) -> String {
+        format!(
+            "frame: {}
+arrival time: {}
+link type: {}
+captured length: {}
+packet length: {}",
+            self.id,
+            self.timestamp.format("%Y-%m-%d %H:%M:%S%.6f"),
+            self.link_type_name(),
+            self.packet_header.caplen,
+            self.packet_header.len,
+        )
+    }
+
+    fn get_next(&self) -> LayerData<'_> {
+        match &self.payload {
+            DataLink::Ethernet(x) => LayerData::Layer(x as _),
+            DataLink::LinuxSll(x) => LayerData::Layer(x as _),
+            DataLink::LinuxSll2(x) => LayerData::Layer(x as _),
+            DataLink::Loopback(x) => LayerData::Layer(x as _),
+            DataLink::Raw(x) => x.get_layer_data(),
+            DataLink::Other(x) => LayerData::Data(x),
+        }
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::Frame
+    }
+
+    fn source(&self) -> Cow<'_, str> {
+        Cow::from("na")
+    }
+
+    fn destination(&self) -> Cow<'_, str> {
+        Cow::from("na")
+    }
+
+    fn info(&self) -> String {
+        format!(
+            "{} bytes on wire, link type {}",
+            self.packet_header.len,
+            self.link_type_name()
+        )
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            Field::new("frame.number", self.id as u32),
+            Field::new("frame.len", self.packet_header.len),
+            Field::new("frame.cap_len", self.packet_header.caplen),
+        ]
+    }
+
+    fn tree(&self) -> TreeNode {
+        let label = format!(
+            "Frame {}: {} bytes on wire, {} bytes captured",
+            self.id, self.packet_header.len, self.packet_header.caplen
+        );
+        TreeNode {
+            range: Some(0..self.data.len()),
+            ..TreeNode::from_summary(label, &self.get_summary())
+        }
+    }
+
+    fn header_len(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl Describable for Frame {
+    fn get_long(&self) -> BTreeMap<Protocol, String> {
+        let mut map: BTreeMap<Protocol, String> = BTreeMap::new();
+        for layer in self.layers() {
+            let (protocol, summary) = match layer {
+                LayerData::Layer(layer) => (layer.protocol(), layer.get_summary()),
+                LayerData::Application(app) => (app.protocol(), app.get_summary()),
+                LayerData::Data(_) => continue,
+            };
+            // several messages of one protocol in a segment, one after the other
+            map.entry(protocol)
+                .and_modify(|summaries| {
+                    summaries.push_str("\n\n");
+                    summaries.push_str(&summary);
+                })
+                .or_insert(summary);
+        }
+        map
+    }
+
+    fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    fn get_description(&self) -> Description<'_> {
+        // addresses come from the network layer when there is one, otherwise
+        // from whatever the link layer is
+        let src_dest_layer: &dyn Layer = match self.payload.network() {
+            Some(network) if !matches!(network, Network::Other(_)) => {
+                match network.get_layer_data() {
+                    LayerData::Layer(x) => x,
+                    _ => self as _,
+                }
+            }
+            _ => match self.get_next() {
+                LayerData::Layer(x) => x,
+                _ => self as _,
+            },
+        };
+
+        let mut info_layers = vec![get_innermost_layer(LayerData::Layer(self))];
+        if let Some(Transport::TCP(tcp)) = self.payload.network().and_then(Network::transport) {
+            info_layers.extend({ tcp.more_messages.iter() }.map(Application::get_layer_data));
+        }
+
+        Description {
+            id: self.id,
+            timestamp: self.timestamp,
+            vlan: self.vlan_ids(),
+            src_dest_layer,
+            info_layers,
+        }
+    }
+}
+
+// helper functions
+fn capture_time(header: &pcap::PacketHeader) -> DateTime<Utc> {
+    let nanos = header.ts.tv_usec as u32 * 1_000;
+    // time_t is not i64 on every platform
+    #[allow(clippy::unnecessary_cast)]
+    DateTime::from_timestamp(header.ts.tv_sec as i64, nanos).unwrap_or_default()
+}
+
+fn get_innermost_layer<'a>(mut layer: LayerData<'a>) -> LayerData<'a> {
+    let mut last_layer: Option<&'a dyn Layer> = None;
+
+    while let LayerData::Layer(current_layer) = layer {
+        last_layer = Some(current_layer);
+        layer = current_layer.get_next();
+    }
+
+    match layer {
+        LayerData::Data(_) => {
+            if let Some(last) = last_layer {
+                LayerData::Layer(last)
+            } else {
+                LayerData::Data(&[])
+            }
+        }
+        _ => layer,
+    }
+}
@@ -0,0 +1,196 @@
+use crate::packets::fields::{Field, FieldValue};
+use crate::packets::frame::Frame;
+use crate::packets::packet_traits::Layer;
+use crate::packets::shared_objs::{LayerData, Network, Protocol};
+use crate::packets::tree::TreeNode;
+use pnet::packet::ethernet::EtherType;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+pub struct ArpPacket {
+    pub hardware_type: u16,
+    pub protocol_type: EtherType,
+    pub operation: u16,
+    pub sender_mac: Box<str>,
+    pub sender_ip: Box<str>,
+    pub target_mac: Box<str>,
+    pub target_ip: Box<str>,
+}
+
+impl ArpPacket {
+    pub fn new(packet: &[u8]) -> Option<ArpPacket> {
+        let packet = pnet::packet::arp::ArpPacket::new(packet)?;
+
+        Some(ArpPacket {
+            hardware_type: packet.get_hardware_type().0,
+            protocol_type: packet.get_protocol_type(),
+            operation: packet.get_operation().0,
+            sender_mac: packet.get_sender_hw_addr().to_string().into_boxed_str(),
+            sender_ip: packet.get_sender_proto_addr().to_string().into_boxed_str(),
+            target_mac: packet.get_target_hw_addr().to_string().into_boxed_str(),
+            target_ip: packet.get_target_proto_addr().to_string().into_boxed_str(),
+        })
+    }
+
+    pub fn operation_name(&self) -> &str {
+        match self.operation {
+            1 => "request",
+            2 => "reply",
+            3 => "reverse request",
+            4 => "reverse reply",
+            _ => "unknown",
+        }
+    }
+
+    // an announcement of the sender's own address rather than a question
+    pub fn is_gratuitous(&self) -> bool {
+        self.sender_ip == self.target_ip
+    }
+}
+
+impl Layer for ArpPacket {
+    fn get_summary(&self) -> String {
+        let ArpPacket {
+            hardware_type,
+            protocol_type,
+            operation,
+            sender_mac,
+            sender_ip,
+            target_mac,
+            target_ip,
+        } = self;
+
+        format!(
+            "hardware_type: {hardware_type}
+protocol_type: {protocol_type}
+operation: {} ({operation})
+sender_mac: {sender_mac}
+sender_ip: {sender_ip}
+target_mac: {target_mac}
+target_ip: {target_ip}",
+            self.operation_name()
+        )
+    }
+
+    fn get_next(&self) -> LayerData<'_> {
+        LayerData::Data(&[])
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::ARP
+    }
+
+    fn source(&self) -> Cow<'_, str> {
+        Cow::from(self.sender_mac.to_string())
+    }
+
+    fn destination(&self) -> Cow<'_, str> {
+        Cow::from(self.target_mac.to_string())
+    }
+
+    fn info(&self) -> String {
+        if self.is_gratuitous() {
+            return format!("Gratuitous ARP for {}", self.sender_ip);
+        }
+        match self.operation {
+            1 => format!("Who has {}? Tell {}", self.target_ip, self.sender_ip),
+            2 => format!("{} is at {}", self.sender_ip, self.sender_mac),
+            _ => format!("ARP {}", self.operation_name()),
+        }
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![
+            Field::new("arp.opcode", self.operation),
+            Field::new("arp.hw.type", self.hardware_type),
+            Field::new("arp.proto.type", self.protocol_type.0),
+        ];
+        let addresses = [
+            ("arp.src.hw_mac", FieldValue::mac(&self.sender_mac)),
+            ("arp.src.proto_ipv4", FieldValue::ip(&self.sender_ip)),
+            ("arp.dst.hw_mac", FieldValue::mac(&self.target_mac)),
+            ("arp.dst.proto_ipv4", FieldValue::ip(&self.target_ip)),
+        ];
+        for (name, value) in addresses {
+            fields.extend(value.map(|value| Field::new(name, value)));
+        }
+        fields
+    }
+
+    // laid out for ethernet and ipv4 addresses, the only ones parsed
+    fn tree(&self) -> TreeNode {
+        let label = format!("ARP, {}", self.operation_name());
+        TreeNode::at(label, 0, 28).with_children(vec![
+            TreeNode::at(format!("hardware_type: {}", self.hardware_type), 0, 2),
+            TreeNode::at(format!("protocol_type: {}", self.protocol_type), 2, 2),
+            TreeNode::at(
+                format!("operation: {} ({})", self.operation_name(), self.operation),
+                6,
+                2,
+            ),
+            TreeNode::at(format!("sender_mac: {}", self.sender_mac), 8, 6),
+            TreeNode::at(format!("sender_ip: {}", self.sender_ip), 14, 4),
+            TreeNode::at(format!("target_mac: {}", self.target_mac), 18, 6),
+            TreeNode::at(format!("target_ip: {}", self.target_ip), 24, 4),
+        ])
+    }
+
+    fn header_len(&self) -> Option<usize> {
+        Some(28)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ArpBinding {
+    pub frames: usize,
+    pub first_seen: i32,
+    pub last_seen: i32,
+}
+
+// ip -> mac bindings learned from the sender fields of every ARP packet seen
+#[derive(Debug, Clone, Default)]
+pub struct ArpTable {
+    pub bindings: BTreeMap<Box<str>, BTreeMap<Box<str>, ArpBinding>>,
+    processed: usize,
+    // `Sniffer::generation` of the capture the bindings came from
+    generation: u32,
+}
+
+impl ArpTable {
+    // only looks at frames it hasn't seen yet, starts over when `generation`
+    // says a new capture was started
+    pub fn update(&mut self, frames: &[Frame], generation: u32) {
+        if generation != self.generation {
+            *self = ArpTable {
+                generation,
+                ..Default::default()
+            };
+        }
+
+        for frame in &frames[self.processed..] {
+            let Some(Network::Arp(arp)) = frame.payload.network() else {
+                continue;
+            };
+            // probes don't claim an address yet
+            if &*arp.sender_ip == "0.0.0.0" {
+                continue;
+            }
+            let binding = { self.bindings.entry(arp.sender_ip.clone()) }
+                .or_default()
+                .entry(arp.sender_mac.clone())
+                .or_insert_with(|| ArpBinding {
+                    first_seen: frame.id,
+                    ..Default::default()
+                });
+            binding.frames += 1;
+            binding.last_seen = frame.id;
+        }
+        self.processed = frames.len();
+    }
+
+    // more than one MAC answering for the same IP is what spoofing looks like
+    pub fn is_conflicted(&self, ip: &str) -> bool {
+        self.bindings.get(ip).is_some_and(|macs| macs.len() > 1)
+    }
+}
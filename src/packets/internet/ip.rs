@@ -1,6 +1,8 @@
+use crate::packets::fields::{Field, FieldValue};
 use crate::packets::packet_traits::Layer;
 use crate::packets::shared_objs::{LayerData, Protocol, Transport};
-use crate::packets::transport::{tcp::TcpPacket, udp::UdpPacket};
+use crate::packets::transport::transport_parser::parse_transport_layer;
+use crate::packets::tree::TreeNode;
 use pnet::packet::Packet;
 use pnet::packet::{
     ip::{IpNextHeaderProtocol, IpNextHeaderProtocols},
@@ -11,6 +13,8 @@ use std::borrow::Cow;
 #[derive(Debug, Clone)]
 pub struct Ipv4Header {
     pub version_ihl: u8,
+    // in bytes, options included
+    pub header_length: u8,
     pub dscp: u8,
     pub ecn: u8,
     pub total_length: u16,
@@ -27,9 +31,9 @@ pub struct Ipv4Header {
 
 #[derive(Clone, Default, Debug)]
 pub struct Ipv4Flags {
-    reserved: bool,
-    dontfrag: bool,
-    morefrag: bool,
+    pub reserved: bool,
+    pub dontfrag: bool,
+    pub morefrag: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -97,6 +101,10 @@ impl Ipv4Options {
 pub struct Ipv4Packet {
     pub header: Ipv4Header,
     pub payload: Transport,
+    // set on every fragment once the whole datagram has been seen
+    pub reassembled_in: Option<i32>,
+    // on the frame that completed reassembly, the frames it was built from
+    pub fragments: Box<[i32]>,
 }
 
 impl Ipv4Packet {
@@ -105,10 +113,11 @@ impl Ipv4Packet {
 
         let header = Ipv4Header {
             version_ihl: packet.get_version(),
+            header_length: packet.get_header_length() * 4,
             dscp: packet.get_dscp(),
             ecn: packet.get_ecn(),
             total_length: packet.get_total_length(),
-            identification: packet.get_total_length(),
+            identification: packet.get_identification(),
             options: Ipv4Header::set_options(packet.get_options_iter()),
             flags_fragment_offset: packet.get_fragment_offset(),
             time_to_live: packet.get_ttl(),
@@ -119,14 +128,25 @@ impl Ipv4Packet {
             flags: Ipv4Header::set_flags(packet.get_flags()),
         };
 
-        let payload = match header.next_header {
-            IpNextHeaderProtocols::Tcp => TcpPacket::new(packet.payload()).map(Transport::TCP),
-            IpNextHeaderProtocols::Udp => UdpPacket::new(packet.payload()).map(Transport::UDP),
-            _ => None,
-        }
-        .unwrap_or_else(|| Transport::Other(packet.payload().to_vec().into_boxed_slice()));
+        // a fragment only holds part of the transport segment, it gets dissected
+        // once reassembled
+        let is_fragment = header.flags.morefrag || header.flags_fragment_offset != 0;
+        let payload = if is_fragment {
+            Transport::Other(packet.payload().to_vec().into_boxed_slice())
+        } else {
+            parse_transport_layer(header.next_header, packet.payload())
+        };
+
+        Some(Ipv4Packet {
+            header,
+            payload,
+            reassembled_in: None,
+            fragments: Box::new([]),
+        })
+    }
 
-        Some(Ipv4Packet { header, payload })
+    pub fn is_fragment(&self) -> bool {
+        self.header.flags.morefrag || self.header.flags_fragment_offset != 0
     }
 }
 
@@ -134,6 +154,7 @@ impl Layer for Ipv4Packet {
     fn get_summary(&self) -> String {
         let Ipv4Header {
             version_ihl,
+            header_length,
             dscp,
             ecn,
             total_length,
@@ -158,8 +179,21 @@ impl Layer for Ipv4Packet {
             .collect::<Vec<&str>>()
             .join("\n");
 
+        let reassembly_string = if !self.fragments.is_empty() {
+            let frames = { self.fragments.iter() }
+                .map(i32::to_string)
+                .collect::<Vec<String>>()
+                .join(", ");
+            format!("\nreassembled from frames: {frames}")
+        } else if let Some(frame) = self.reassembled_in {
+            format!("\nreassembled in: {frame}")
+        } else {
+            String::new()
+        };
+
         format!(
             "version: {version_ihl}
+header_length: {header_length}
 dscp: {dscp}
 ecn: {ecn}
 total_length: {total_length}
@@ -171,19 +205,15 @@ source_address: {source_address}
 destination_address: {destination_address}
 next_header: protocol : {next_header}
 flags: reserved : {reserved}, dont fragment : {dontfrag},  more fragment : {morefrag}
-options: {options_string}"
+options: {options_string}{reassembly_string}"
         )
     }
     fn protocol(&self) -> Protocol {
         Protocol::IPv4
     }
 
-    fn get_next(&self) -> LayerData {
-        match &self.payload {
-            Transport::TCP(x) => LayerData::Layer(x as _),
-            Transport::UDP(x) => LayerData::Layer(x as _),
-            Transport::Other(x) => LayerData::Data(x),
-        }
+    fn get_next(&self) -> LayerData<'_> {
+        self.payload.get_layer_data()
     }
 
     fn source(&self) -> Cow<'_, str> {
@@ -195,22 +225,442 @@ options: {options_string}"
     }
 
     fn info(&self) -> String {
+        if self.is_fragment() && self.fragments.is_empty() {
+            let reassembled = match self.reassembled_in {
+                Some(frame) => format!(" (reassembled in {frame})"),
+                None => String::new(),
+            };
+            return format!(
+                "Fragmented IP protocol (proto={}, off={}, id={:#06x}){reassembled}",
+                self.header.next_header,
+                self.header.flags_fragment_offset as usize * 8,
+                self.header.identification
+            );
+        }
         format!(
             "flags: MoreFrag: {} DontFrag: {} Reserved: {}",
             self.header.flags.morefrag, self.header.flags.dontfrag, self.header.flags.reserved
         )
     }
+
+    fn fields(&self) -> Vec<Field> {
+        let header = &self.header;
+        let mut fields = address_fields(
+            ["ip.src", "ip.dst", "ip.addr"],
+            &header.source_address,
+            &header.destination_address,
+        );
+        fields.extend([
+            Field::new("ip.version", header.version_ihl),
+            Field::new("ip.dsfield.dscp", header.dscp),
+            Field::new("ip.dsfield.ecn", header.ecn),
+            Field::new("ip.len", header.total_length),
+            Field::new("ip.id", header.identification),
+            Field::new("ip.flags.df", header.flags.dontfrag),
+            Field::new("ip.flags.mf", header.flags.morefrag),
+            // the header counts in 8 byte units
+            Field::new("ip.frag_offset", header.flags_fragment_offset as u32 * 8),
+            Field::new("ip.ttl", header.time_to_live),
+            Field::new("ip.proto", header.next_header.0),
+        ]);
+        fields
+    }
+
+    fn tree(&self) -> TreeNode {
+        let header = &self.header;
+        let Ipv4Flags {
+            reserved,
+            dontfrag,
+            morefrag,
+        } = header.flags;
+        let header_length = header.header_length as usize;
+
+        let options = { header.options.iter() }
+            .map(|option| TreeNode::new(option.description()))
+            .collect();
+        let mut children = vec![
+            TreeNode::at(format!("version: {}", header.version_ihl), 0, 1),
+            TreeNode::at(format!("header_length: {header_length}"), 0, 1),
+            TreeNode::at(format!("dscp: {}", header.dscp), 1, 1),
+            TreeNode::at(format!("ecn: {}", header.ecn), 1, 1),
+            TreeNode::at(format!("total_length: {}", header.total_length), 2, 2),
+            TreeNode::at(format!("identification: {}", header.identification), 4, 2),
+            TreeNode::at(
+                format!("flags: reserved : {reserved}, dont fragment : {dontfrag}, more fragment : {morefrag}"),
+                6,
+                1,
+            ),
+            TreeNode::at(
+                format!("fragment_offset: {}", header.flags_fragment_offset as usize * 8),
+                6,
+                2,
+            ),
+            TreeNode::at(format!("time_to_live: {}", header.time_to_live), 8, 1),
+            TreeNode::at(format!("next_header: protocol : {}", header.next_header), 9, 1),
+            TreeNode::at(format!("header_checksum: {}", header.header_checksum), 10, 2),
+            TreeNode::at(format!("source_address: {}", header.source_address), 12, 4),
+            TreeNode::at(format!("destination_address: {}", header.destination_address), 16, 4),
+        ];
+        if header_length > 20 {
+            children.push(TreeNode::at("options", 20, header_length - 20).with_children(options));
+        }
+        if !self.fragments.is_empty() {
+            let frames = { self.fragments.iter() }
+                .map(i32::to_string)
+                .collect::<Vec<String>>()
+                .join(", ");
+            children.push(TreeNode::new(format!("reassembled from frames: {frames}")));
+        } else if let Some(frame) = self.reassembled_in {
+            children.push(TreeNode::new(format!("reassembled in: {frame}")));
+        }
+
+        let label = format!(
+            "IPv4, Src: {}, Dst: {}",
+            header.source_address, header.destination_address
+        );
+        TreeNode::at(label, 0, header_length).with_children(children)
+    }
+
+    // a reassembled datagram's transport layer comes from several frames
+    fn header_len(&self) -> Option<usize> {
+        match self.fragments.is_empty() {
+            true => Some(self.header.header_length as usize),
+            false => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Ipv6Header {
-    pub payload: Box<[u8]>,
+    pub version: u8,
     pub traffic_class: u8,
-    pub flow_label: u16,
+    pub flow_label: u32,
     pub payload_length: u16,
-    //pub next_header: ProtocolDescriptor,
+    pub next_header: IpNextHeaderProtocol,
     pub hop_limit: u8,
     pub source: Box<str>,
     pub destination: Box<str>,
-    pub version: u8,
+}
+
+#[derive(Debug, Clone)]
+pub enum Ipv6Extension {
+    HopByHop {
+        next_header: IpNextHeaderProtocol,
+        length: usize,
+    },
+    Routing {
+        next_header: IpNextHeaderProtocol,
+        length: usize,
+        routing_type: u8,
+        segments_left: u8,
+    },
+    Fragment {
+        next_header: IpNextHeaderProtocol,
+        fragment_offset: u16,
+        more_fragments: bool,
+        identification: u32,
+    },
+    DestinationOptions {
+        next_header: IpNextHeaderProtocol,
+        length: usize,
+    },
+}
+
+impl Ipv6Extension {
+    // walks the extension header chain, returns the headers it found plus the
+    // protocol and offset of whatever comes after them
+    fn parse_chain(
+        mut next_header: IpNextHeaderProtocol,
+        data: &[u8],
+    ) -> (Box<[Ipv6Extension]>, IpNextHeaderProtocol, usize) {
+        let mut extensions = Vec::new();
+        let mut offset = 0;
+
+        while let Some(ext) = data.get(offset..) {
+            let extension = match next_header {
+                IpNextHeaderProtocols::Hopopt
+                | IpNextHeaderProtocols::Ipv6Route
+                | IpNextHeaderProtocols::Ipv6Opts => {
+                    let [next, length, ..] = *ext else {
+                        break;
+                    };
+                    // length is in 8 octet units, not counting the first 8
+                    let length = (length as usize + 1) * 8;
+                    if ext.len() < length {
+                        break;
+                    }
+                    let next = IpNextHeaderProtocol(next);
+                    match next_header {
+                        IpNextHeaderProtocols::Hopopt => Ipv6Extension::HopByHop {
+                            next_header: next,
+                            length,
+                        },
+                        IpNextHeaderProtocols::Ipv6Route => Ipv6Extension::Routing {
+                            next_header: next,
+                            length,
+                            routing_type: ext[2],
+                            segments_left: ext[3],
+                        },
+                        _ => Ipv6Extension::DestinationOptions {
+                            next_header: next,
+                            length,
+                        },
+                    }
+                }
+                IpNextHeaderProtocols::Ipv6Frag => {
+                    let Some(&[next, _, off_hi, off_lo, id0, id1, id2, id3]) = ext.get(..8) else {
+                        break;
+                    };
+                    let offset_flags = u16::from_be_bytes([off_hi, off_lo]);
+                    Ipv6Extension::Fragment {
+                        next_header: IpNextHeaderProtocol(next),
+                        fragment_offset: offset_flags >> 3,
+                        more_fragments: offset_flags & 1 != 0,
+                        identification: u32::from_be_bytes([id0, id1, id2, id3]),
+                    }
+                }
+                _ => break,
+            };
+
+            next_header = extension.next_header();
+            offset += extension.length();
+            extensions.push(extension);
+        }
+
+        (extensions.into_boxed_slice(), next_header, offset)
+    }
+
+    fn next_header(&self) -> IpNextHeaderProtocol {
+        match self {
+            Ipv6Extension::HopByHop { next_header, .. }
+            | Ipv6Extension::Routing { next_header, .. }
+            | Ipv6Extension::Fragment { next_header, .. }
+            | Ipv6Extension::DestinationOptions { next_header, .. } => *next_header,
+        }
+    }
+
+    fn length(&self) -> usize {
+        match self {
+            Ipv6Extension::HopByHop { length, .. }
+            | Ipv6Extension::Routing { length, .. }
+            | Ipv6Extension::DestinationOptions { length, .. } => *length,
+            Ipv6Extension::Fragment { .. } => 8,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Ipv6Extension::HopByHop { .. } => "Hop-by-Hop Options",
+            Ipv6Extension::Routing { .. } => "Routing",
+            Ipv6Extension::Fragment { .. } => "Fragment",
+            Ipv6Extension::DestinationOptions { .. } => "Destination Options",
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            Ipv6Extension::Routing {
+                routing_type,
+                segments_left,
+                ..
+            } => format!(
+                "{}: type {routing_type}, segments left {segments_left}",
+                self.name()
+            ),
+            Ipv6Extension::Fragment {
+                fragment_offset,
+                more_fragments,
+                identification,
+                ..
+            } => format!(
+                "{}: id {identification:#010x}, offset {fragment_offset}, more fragments {more_fragments}",
+                self.name()
+            ),
+            _ => format!("{}: {} bytes", self.name(), self.length()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Ipv6Packet {
+    pub header: Ipv6Header,
+    pub extensions: Box<[Ipv6Extension]>,
+    pub payload: Transport,
+}
+
+impl Ipv6Packet {
+    pub fn new(packet: &[u8]) -> Option<Ipv6Packet> {
+        let packet = pnet::packet::ipv6::Ipv6Packet::new(packet)?;
+
+        let header = Ipv6Header {
+            version: packet.get_version(),
+            traffic_class: packet.get_traffic_class(),
+            flow_label: packet.get_flow_label(),
+            payload_length: packet.get_payload_length(),
+            next_header: packet.get_next_header(),
+            hop_limit: packet.get_hop_limit(),
+            source: packet.get_source().to_string().into_boxed_str(),
+            destination: packet.get_destination().to_string().into_boxed_str(),
+        };
+
+        let (extensions, next_header, offset) =
+            Ipv6Extension::parse_chain(header.next_header, packet.payload());
+        let data = &packet.payload()[offset..];
+
+        // only an unfragmented packet, or the first fragment, starts with a transport header
+        let is_fragment = extensions.iter().any(|ext| {
+            matches!(ext, Ipv6Extension::Fragment { fragment_offset, more_fragments, .. }
+                if *fragment_offset != 0 || *more_fragments)
+        });
+        let payload = if is_fragment {
+            Transport::Other(data.to_vec().into_boxed_slice())
+        } else {
+            parse_transport_layer(next_header, data)
+        };
+
+        Some(Ipv6Packet {
+            header,
+            extensions,
+            payload,
+        })
+    }
+
+    // protocol of the payload once the extension headers are skipped
+    pub fn upper_layer_protocol(&self) -> IpNextHeaderProtocol {
+        { self.extensions.last() }.map_or(self.header.next_header, Ipv6Extension::next_header)
+    }
+}
+
+impl Layer for Ipv6Packet {
+    fn get_summary(&self) -> String {
+        let Ipv6Header {
+            version,
+            traffic_class,
+            flow_label,
+            payload_length,
+            next_header,
+            hop_limit,
+            source,
+            destination,
+        } = &self.header;
+
+        let extensions_string = { self.extensions.iter() }
+            .map(Ipv6Extension::description)
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!(
+            "version: {version}
+traffic_class: {traffic_class}
+flow_label: {flow_label:#07x}
+payload_length: {payload_length}
+next_header: protocol : {next_header}
+hop_limit: {hop_limit}
+source_address: {source}
+destination_address: {destination}
+extension headers: {extensions_string}"
+        )
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::IPv6
+    }
+
+    fn get_next(&self) -> LayerData<'_> {
+        self.payload.get_layer_data()
+    }
+
+    fn source(&self) -> Cow<'_, str> {
+        Cow::from(self.header.source.to_string())
+    }
+
+    fn destination(&self) -> Cow<'_, str> {
+        Cow::from(self.header.destination.to_string())
+    }
+
+    fn info(&self) -> String {
+        if self.extensions.is_empty() {
+            format!("next header {}", self.header.next_header)
+        } else {
+            let chain = { self.extensions.iter() }
+                .map(Ipv6Extension::name)
+                .collect::<Vec<&str>>()
+                .join(", ");
+            format!("{chain}, next header {}", self.upper_layer_protocol())
+        }
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        let header = &self.header;
+        let mut fields = address_fields(
+            ["ipv6.src", "ipv6.dst", "ipv6.addr"],
+            &header.source,
+            &header.destination,
+        );
+        fields.extend([
+            Field::new("ipv6.tclass", header.traffic_class),
+            Field::new("ipv6.flow", header.flow_label),
+            Field::new("ipv6.plen", header.payload_length),
+            Field::new("ipv6.nxt", header.next_header.0),
+            Field::new("ipv6.hlim", header.hop_limit),
+        ]);
+        fields
+    }
+
+    fn tree(&self) -> TreeNode {
+        let header = &self.header;
+        let mut children = vec![
+            TreeNode::at(format!("version: {}", header.version), 0, 1),
+            TreeNode::at(format!("traffic_class: {}", header.traffic_class), 0, 2),
+            TreeNode::at(format!("flow_label: {:#07x}", header.flow_label), 1, 3),
+            TreeNode::at(format!("payload_length: {}", header.payload_length), 4, 2),
+            TreeNode::at(
+                format!("next_header: protocol : {}", header.next_header),
+                6,
+                1,
+            ),
+            TreeNode::at(format!("hop_limit: {}", header.hop_limit), 7, 1),
+            TreeNode::at(format!("source_address: {}", header.source), 8, 16),
+            TreeNode::at(
+                format!("destination_address: {}", header.destination),
+                24,
+                16,
+            ),
+        ];
+        let mut offset = 40;
+        for extension in self.extensions.iter() {
+            children.push(TreeNode::at(
+                extension.description(),
+                offset,
+                extension.length(),
+            ));
+            offset += extension.length();
+        }
+
+        let label = format!("IPv6, Src: {}, Dst: {}", header.source, header.destination);
+        TreeNode::at(label, 0, offset).with_children(children)
+    }
+
+    fn header_len(&self) -> Option<usize> {
+        Some(
+            40 + { self.extensions.iter() }
+                .map(Ipv6Extension::length)
+                .sum::<usize>(),
+        )
+    }
+}
+
+// source, destination, and both again under the either-direction name
+fn address_fields(names: [&'static str; 3], source: &str, destination: &str) -> Vec<Field> {
+    let [source_name, destination_name, either_name] = names;
+    let (Some(source), Some(destination)) = (FieldValue::ip(source), FieldValue::ip(destination))
+    else {
+        return vec![];
+    };
+    vec![
+        Field::new(source_name, source.clone()),
+        Field::new(destination_name, destination.clone()),
+        Field::new(either_name, source),
+        Field::new(either_name, destination),
+    ]
 }
@@ -1 +1,3 @@
+pub mod arp;
 pub mod ip;
+pub mod net_parser;
@@ -0,0 +1,26 @@
+use crate::packets::dissectors;
+use crate::packets::internet::{
+    arp::ArpPacket,
+    ip::{Ipv4Packet, Ipv6Packet},
+};
+use crate::packets::shared_objs::Network;
+use pnet::packet::ethernet::{EtherType, EtherTypes};
+
+pub fn parse_network_layer(ether_type: EtherType, data: &[u8]) -> Network {
+    match ether_type {
+        EtherTypes::Ipv4 => Ipv4Packet::new(data).map(Network::IPv4),
+        EtherTypes::Ipv6 => Ipv6Packet::new(data).map(Network::IPv6),
+        EtherTypes::Arp => ArpPacket::new(data).map(Network::Arp),
+        _ => dissectors::dissect_ether_type(ether_type.0, data).map(Network::Custom),
+    }
+    .unwrap_or_else(|| Network::Other(data.to_vec().into_boxed_slice()))
+}
+
+// raw IP captures have no link header at all, the version nibble says what follows
+pub fn parse_raw_ip(data: &[u8]) -> Network {
+    match data.first().map(|b| b >> 4) {
+        Some(4) => parse_network_layer(EtherTypes::Ipv4, data),
+        Some(6) => parse_network_layer(EtherTypes::Ipv6, data),
+        _ => Network::Other(data.to_vec().into_boxed_slice()),
+    }
+}
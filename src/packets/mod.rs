@@ -1,7 +1,11 @@
 pub mod data_link;
 
 pub mod application;
+pub mod dissectors;
+pub mod fields;
+pub mod frame;
 pub mod internet;
 pub mod packet_traits;
 pub mod shared_objs;
 pub mod transport;
+pub mod tree;
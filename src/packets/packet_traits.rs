@@ -1,4 +1,6 @@
+use crate::packets::fields::Field;
 use crate::packets::shared_objs::{Description, LayerData};
+use crate::packets::tree::TreeNode;
 use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
@@ -18,6 +20,22 @@ pub trait Layer: Send + Sync + Debug {
     fn info(&self) -> String {
         "Unknown protocol, info not available".to_owned()
     }
+
+    // see `packets::fields`
+    fn fields(&self) -> Vec<Field> {
+        vec![]
+    }
+
+    // see `packets::tree`, without byte ranges the summary lines are listed
+    fn tree(&self) -> TreeNode {
+        TreeNode::from_summary(self.protocol().to_string(), &self.get_summary())
+    }
+
+    // bytes from the start of the layer to where the next one starts, None
+    // when the next layer isn't read straight from this frame
+    fn header_len(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub trait AppLayer: Send + Sync + Debug {
@@ -38,6 +56,14 @@ pub trait AppLayer: Send + Sync + Debug {
     fn info(&self) -> String {
         "Unknown protocol, info not available".to_owned()
     }
+
+    fn fields(&self) -> Vec<Field> {
+        vec![]
+    }
+
+    fn tree(&self) -> TreeNode {
+        TreeNode::from_summary(self.protocol().to_string(), &self.get_summary())
+    }
 }
 
 pub trait Describable: Send + Sync + Debug + Layer {
@@ -4,19 +4,57 @@ use crate::packets::application::{
     tls::Tls,
 };
 use crate::packets::{
-    data_link::ethernet::EthernetFrame,
-    internet::ip::Ipv4Packet,
+    data_link::{
+        ethernet::EthernetFrame,
+        loopback::LoopbackPacket,
+        sll::{Sll2Packet, SllPacket},
+    },
+    fields::Field,
+    internet::{
+        arp::ArpPacket,
+        ip::{Ipv4Packet, Ipv6Packet},
+    },
     packet_traits::{AppLayer, Layer},
-    transport::{tcp::TcpPacket, udp::UdpPacket},
+    transport::{icmp::IcmpPacket, tcp::TcpPacket, udp::UdpPacket},
+    tree::TreeNode,
 };
+use chrono::{DateTime, Utc};
 use std::fmt;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct Description<'a> {
     pub id: i32,
-    pub timestamp: &'a str,
+    pub timestamp: DateTime<Utc>,
+    pub vlan: Option<String>,
     pub src_dest_layer: &'a dyn Layer,
-    pub info_layer: LayerData<'a>,
+    // the innermost layer, then any more messages from the same TCP segment
+    pub info_layers: Vec<LayerData<'a>>,
+}
+
+impl Description<'_> {
+    // what the packet table shows, in the order of `Description::COLUMNS`
+    pub const COLUMNS: [&'static str; 6] =
+        ["id", "timestamp", "source", "destination", "vlan", "info"];
+
+    pub fn columns(&self) -> [String; 6] {
+        let info = { self.info_layers.iter() }
+            .map(|layer| match layer {
+                LayerData::Layer(layer) => layer.info(),
+                LayerData::Application(layer) => layer.info(),
+                LayerData::Data(data) => format!("{} bytes", data.len()),
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        [
+            self.id.to_string(),
+            self.timestamp.format("%Y-%m-%d %H:%M:%S%.6f").to_string(),
+            self.src_dest_layer.source().into_owned(),
+            self.src_dest_layer.destination().into_owned(),
+            self.vlan.clone().unwrap_or_default(),
+            info,
+        ]
+    }
 }
 
 pub enum Data {
@@ -28,25 +66,124 @@ pub enum Data {
 pub enum Transport {
     UDP(UdpPacket),
     TCP(TcpPacket),
+    ICMP(IcmpPacket),
+    // from a dissector registered with `dissectors::register_ip_protocol`
+    Custom(Box<dyn Layer>),
     Other(Box<[u8]>),
 }
 #[derive(Debug, Clone)]
 pub enum Application {
-    //HttpRequest(HttpRequest),
-    //HttpResponse(HttpResponse),
+    HttpRequest(HttpRequest),
+    HttpResponse(HttpResponse),
     Dns(DnsMessage),
-    //Tls(Tls),
+    Tls(Tls),
+    // from dissectors registered with `dissectors::register_app_dissector`,
+    // Arc so the message stays Clone
+    Custom(Arc<dyn AppLayer>),
+    CustomLayer(Arc<dyn Layer>),
     Other(Box<[u8]>),
 }
 // enum Physical {}
 
+#[derive(Debug)]
+pub enum DataLink {
+    Ethernet(EthernetFrame),
+    LinuxSll(SllPacket),
+    LinuxSll2(Sll2Packet),
+    Loopback(LoopbackPacket),
+    // raw IP captures, there is no link layer header
+    Raw(Network),
+    Other(Box<[u8]>),
+}
+
 #[derive(Debug)]
 pub enum Network {
     IPv4(Ipv4Packet),
-    // IPv6(Ipv6Packet),
+    IPv6(Ipv6Packet),
+    Arp(ArpPacket),
+    // from a dissector registered with `dissectors::register_ether_type`
+    Custom(Box<dyn Layer>),
     Other(Box<[u8]>),
 }
 
+impl DataLink {
+    pub fn network(&self) -> Option<&Network> {
+        match self {
+            DataLink::Ethernet(x) => Some(&x.payload),
+            DataLink::LinuxSll(x) => Some(&x.payload),
+            DataLink::LinuxSll2(x) => Some(&x.payload),
+            DataLink::Loopback(x) => Some(&x.payload),
+            DataLink::Raw(x) => Some(x),
+            DataLink::Other(_) => None,
+        }
+    }
+
+    pub fn network_mut(&mut self) -> Option<&mut Network> {
+        match self {
+            DataLink::Ethernet(x) => Some(&mut x.payload),
+            DataLink::LinuxSll(x) => Some(&mut x.payload),
+            DataLink::LinuxSll2(x) => Some(&mut x.payload),
+            DataLink::Loopback(x) => Some(&mut x.payload),
+            DataLink::Raw(x) => Some(x),
+            DataLink::Other(_) => None,
+        }
+    }
+}
+
+impl Network {
+    pub fn get_layer_data(&self) -> LayerData<'_> {
+        match self {
+            Network::IPv4(x) => LayerData::Layer(x as _),
+            Network::IPv6(x) => LayerData::Layer(x as _),
+            Network::Arp(x) => LayerData::Layer(x as _),
+            Network::Custom(x) => LayerData::Layer(&**x),
+            Network::Other(x) => LayerData::Data(x),
+        }
+    }
+
+    pub fn transport(&self) -> Option<&Transport> {
+        match self {
+            Network::IPv4(x) => Some(&x.payload),
+            Network::IPv6(x) => Some(&x.payload),
+            Network::Arp(_) | Network::Custom(_) | Network::Other(_) => None,
+        }
+    }
+
+    pub fn transport_mut(&mut self) -> Option<&mut Transport> {
+        match self {
+            Network::IPv4(x) => Some(&mut x.payload),
+            Network::IPv6(x) => Some(&mut x.payload),
+            Network::Arp(_) | Network::Custom(_) | Network::Other(_) => None,
+        }
+    }
+}
+
+impl Application {
+    pub fn get_layer_data(&self) -> LayerData<'_> {
+        match self {
+            Application::HttpRequest(request) => LayerData::Application(request),
+            Application::HttpResponse(response) => LayerData::Application(response),
+            Application::Dns(dns_message) => LayerData::Application(dns_message),
+            Application::Other(bytes) => LayerData::Data(bytes),
+            Application::Tls(tls) => LayerData::Application(tls),
+            Application::Custom(x) => LayerData::Application(&**x),
+            Application::CustomLayer(x) => LayerData::Layer(&**x),
+        }
+    }
+}
+
+impl Transport {
+    pub fn get_layer_data(&self) -> LayerData<'_> {
+        match self {
+            Transport::TCP(x) => LayerData::Layer(x as _),
+            Transport::UDP(x) => LayerData::Layer(x as _),
+            Transport::ICMP(x) => LayerData::Layer(x as _),
+            Transport::Custom(x) => LayerData::Layer(&**x),
+            Transport::Other(x) => LayerData::Data(x),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum LayerData<'a> {
     Layer(&'a dyn Layer),
@@ -54,28 +191,104 @@ pub enum LayerData<'a> {
     Data(&'a [u8]),
 }
 
+impl LayerData<'_> {
+    pub fn protocol(&self) -> Option<Protocol> {
+        match self {
+            LayerData::Layer(layer) => Some(layer.protocol()),
+            LayerData::Application(layer) => Some(layer.protocol()),
+            LayerData::Data(_) => None,
+        }
+    }
+
+    pub fn fields(&self) -> Vec<Field> {
+        match self {
+            LayerData::Layer(layer) => layer.fields(),
+            LayerData::Application(layer) => layer.fields(),
+            LayerData::Data(_) => vec![],
+        }
+    }
+
+    pub fn tree(&self) -> TreeNode {
+        match self {
+            LayerData::Layer(layer) => layer.tree(),
+            LayerData::Application(layer) => layer.tree(),
+            LayerData::Data(data) => {
+                TreeNode::at(format!("Data: {} bytes", data.len()), 0, data.len())
+            }
+        }
+    }
+
+    pub fn header_len(&self) -> Option<usize> {
+        match self {
+            LayerData::Layer(layer) => layer.header_len(),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Protocol {
+    Frame,
     Ethernet,
+    LinuxSll,
+    LinuxSll2,
+    Loopback,
     IPv4,
     IPv6,
+    ARP,
     TCP,
     UDP,
+    ICMP,
+    ICMPv6,
     DNS,
     HTTP,
     TLS,
+    // anything from a registered dissector, by name
+    Custom(&'static str),
+}
+
+impl Protocol {
+    // what display filters and field names call it, "ip", "tcp", "tls", ...
+    pub fn filter_name(&self) -> String {
+        match self {
+            Protocol::Frame => "frame",
+            Protocol::Ethernet => "eth",
+            Protocol::LinuxSll | Protocol::LinuxSll2 => "sll",
+            Protocol::Loopback => "null",
+            Protocol::IPv4 => "ip",
+            Protocol::IPv6 => "ipv6",
+            Protocol::ARP => "arp",
+            Protocol::TCP => "tcp",
+            Protocol::UDP => "udp",
+            Protocol::ICMP => "icmp",
+            Protocol::ICMPv6 => "icmpv6",
+            Protocol::DNS => "dns",
+            Protocol::HTTP => "http",
+            Protocol::TLS => "tls",
+            Protocol::Custom(name) => return name.to_lowercase(),
+        }
+        .to_owned()
+    }
 }
 impl fmt::Display for Protocol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            Protocol::Frame => write!(f, "Frame"),
             Protocol::Ethernet => write!(f, "Ethernet"),
+            Protocol::LinuxSll => write!(f, "Linux cooked"),
+            Protocol::LinuxSll2 => write!(f, "Linux cooked v2"),
+            Protocol::Loopback => write!(f, "Loopback"),
             Protocol::IPv4 => write!(f, "IPv4"),
             Protocol::IPv6 => write!(f, "IPv6"),
+            Protocol::ARP => write!(f, "Arp"),
             Protocol::TCP => write!(f, "Tcp"),
             Protocol::UDP => write!(f, "Udp"),
+            Protocol::ICMP => write!(f, "Icmp"),
+            Protocol::ICMPv6 => write!(f, "Icmpv6"),
             Protocol::DNS => write!(f, "Dns"),
             Protocol::HTTP => write!(f, "Http"),
             Protocol::TLS => write!(f, "Tls"),
+            Protocol::Custom(name) => write!(f, "{name}"),
         }
     }
 }
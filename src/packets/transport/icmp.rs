@@ -0,0 +1,342 @@
+use crate::packets::fields::Field;
+use crate::packets::internet::ip::{Ipv4Packet, Ipv6Packet};
+use crate::packets::packet_traits::Layer;
+use crate::packets::shared_objs::{LayerData, Network, Protocol};
+use crate::packets::tree::TreeNode;
+use chrono::Duration;
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcmpVersion {
+    V4,
+    V6,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IcmpEcho {
+    pub identifier: u16,
+    pub sequence: u16,
+    // filled in once the matching request or reply has been captured
+    pub request_in: Option<i32>,
+    pub reply_in: Option<i32>,
+    pub round_trip: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct IcmpPacket {
+    pub version: IcmpVersion,
+    pub icmp_type: u8,
+    pub code: u8,
+    pub checksum: u16,
+    pub echo: Option<IcmpEcho>,
+    // error messages carry the start of the packet that caused them
+    pub quoted: Option<Box<Network>>,
+    pub payload: Box<[u8]>,
+}
+
+impl IcmpPacket {
+    pub fn new(version: IcmpVersion, packet: &[u8]) -> Option<IcmpPacket> {
+        let [icmp_type, code, checksum_hi, checksum_lo, rest @ ..] = packet else {
+            return None;
+        };
+        let (icmp_type, code) = (*icmp_type, *code);
+        // everything after the type specific 4 bytes
+        let payload = rest.get(4..).unwrap_or_default();
+
+        let is_echo = match version {
+            IcmpVersion::V4 => matches!(icmp_type, 0 | 8),
+            IcmpVersion::V6 => matches!(icmp_type, 128 | 129),
+        };
+        let echo = match rest {
+            [id_hi, id_lo, seq_hi, seq_lo, ..] if is_echo => Some(IcmpEcho {
+                identifier: u16::from_be_bytes([*id_hi, *id_lo]),
+                sequence: u16::from_be_bytes([*seq_hi, *seq_lo]),
+                ..Default::default()
+            }),
+            _ => None,
+        };
+
+        let is_error = match version {
+            IcmpVersion::V4 => matches!(icmp_type, 3 | 4 | 5 | 11 | 12),
+            IcmpVersion::V6 => matches!(icmp_type, 1..=4),
+        };
+        let quoted = if is_error {
+            match version {
+                IcmpVersion::V4 => Ipv4Packet::new(payload).map(Network::IPv4),
+                IcmpVersion::V6 => Ipv6Packet::new(payload).map(Network::IPv6),
+            }
+            .map(Box::new)
+        } else {
+            None
+        };
+
+        Some(IcmpPacket {
+            version,
+            icmp_type,
+            code,
+            checksum: u16::from_be_bytes([*checksum_hi, *checksum_lo]),
+            echo,
+            quoted,
+            payload: payload.to_vec().into_boxed_slice(),
+        })
+    }
+
+    pub fn is_echo_request(&self) -> bool {
+        match self.version {
+            IcmpVersion::V4 => self.icmp_type == 8,
+            IcmpVersion::V6 => self.icmp_type == 128,
+        }
+    }
+
+    pub fn is_echo_reply(&self) -> bool {
+        match self.version {
+            IcmpVersion::V4 => self.icmp_type == 0,
+            IcmpVersion::V6 => self.icmp_type == 129,
+        }
+    }
+
+    pub fn type_name(&self) -> &str {
+        match self.version {
+            IcmpVersion::V4 => match self.icmp_type {
+                0 => "Echo (ping) reply",
+                3 => "Destination unreachable",
+                4 => "Source quench",
+                5 => "Redirect",
+                8 => "Echo (ping) request",
+                9 => "Router advertisement",
+                10 => "Router solicitation",
+                11 => "Time-to-live exceeded",
+                12 => "Parameter problem",
+                13 => "Timestamp request",
+                14 => "Timestamp reply",
+                _ => "Unknown",
+            },
+            IcmpVersion::V6 => match self.icmp_type {
+                1 => "Destination unreachable",
+                2 => "Packet too big",
+                3 => "Time exceeded",
+                4 => "Parameter problem",
+                128 => "Echo (ping) request",
+                129 => "Echo (ping) reply",
+                130 => "Multicast listener query",
+                131 => "Multicast listener report",
+                132 => "Multicast listener done",
+                133 => "Router solicitation",
+                134 => "Router advertisement",
+                135 => "Neighbor solicitation",
+                136 => "Neighbor advertisement",
+                137 => "Redirect",
+                143 => "Multicast listener report v2",
+                _ => "Unknown",
+            },
+        }
+    }
+
+    pub fn code_name(&self) -> Option<&str> {
+        let name = match (self.version, self.icmp_type, self.code) {
+            (IcmpVersion::V4, 3, 0) => "Network unreachable",
+            (IcmpVersion::V4, 3, 1) => "Host unreachable",
+            (IcmpVersion::V4, 3, 2) => "Protocol unreachable",
+            (IcmpVersion::V4, 3, 3) => "Port unreachable",
+            (IcmpVersion::V4, 3, 4) => "Fragmentation needed",
+            (IcmpVersion::V4, 3, 5) => "Source route failed",
+            (IcmpVersion::V4, 3, 9 | 10 | 13) => "Administratively prohibited",
+            (IcmpVersion::V4, 5, 0) => "Redirect for network",
+            (IcmpVersion::V4, 5, 1) => "Redirect for host",
+            (IcmpVersion::V4, 11, 0) => "TTL exceeded in transit",
+            (IcmpVersion::V4, 11, 1) => "Fragment reassembly time exceeded",
+            (IcmpVersion::V6, 1, 0) => "No route to destination",
+            (IcmpVersion::V6, 1, 1) => "Administratively prohibited",
+            (IcmpVersion::V6, 1, 2) => "Beyond scope of source address",
+            (IcmpVersion::V6, 1, 3) => "Address unreachable",
+            (IcmpVersion::V6, 1, 4) => "Port unreachable",
+            (IcmpVersion::V6, 3, 0) => "Hop limit exceeded in transit",
+            (IcmpVersion::V6, 3, 1) => "Fragment reassembly time exceeded",
+            _ => return None,
+        };
+        Some(name)
+    }
+}
+
+impl Layer for IcmpPacket {
+    fn get_summary(&self) -> String {
+        let IcmpPacket {
+            version: _,
+            icmp_type,
+            code,
+            checksum,
+            echo,
+            quoted,
+            payload: _,
+        } = self;
+
+        let mut summary = format!(
+            "type: {} ({icmp_type})
+code: {} ({code})
+checksum: {checksum}",
+            self.type_name(),
+            self.code_name().unwrap_or("none"),
+        );
+
+        if let Some(echo) = echo {
+            summary.push_str(&format!(
+                "\nidentifier: {}\nsequence: {}",
+                echo.identifier, echo.sequence
+            ));
+            if let Some(request_in) = echo.request_in {
+                summary.push_str(&format!("\nrequest in: {request_in}"));
+            }
+            if let Some(reply_in) = echo.reply_in {
+                summary.push_str(&format!("\nreply in: {reply_in}"));
+            }
+            if let Some(round_trip) = echo.round_trip {
+                summary.push_str(&format!("\nround trip: {}", format_round_trip(round_trip)));
+            }
+        }
+
+        if let Some(LayerData::Layer(layer)) = quoted.as_deref().map(Network::get_layer_data) {
+            summary.push_str(&format!(
+                "\nquoted {} packet:\n{}",
+                layer.protocol(),
+                layer.get_summary()
+            ));
+        }
+
+        summary
+    }
+
+    fn get_next(&self) -> LayerData<'_> {
+        LayerData::Data(&self.payload)
+    }
+
+    fn protocol(&self) -> Protocol {
+        match self.version {
+            IcmpVersion::V4 => Protocol::ICMP,
+            IcmpVersion::V6 => Protocol::ICMPv6,
+        }
+    }
+
+    fn source(&self) -> Cow<'_, str> {
+        Cow::from("na")
+    }
+
+    fn destination(&self) -> Cow<'_, str> {
+        Cow::from("na")
+    }
+
+    fn info(&self) -> String {
+        let mut info = match self.code_name() {
+            Some(code) => format!("{} ({code})", self.type_name()),
+            None => self.type_name().to_owned(),
+        };
+
+        if let Some(echo) = &self.echo {
+            info.push_str(&format!(
+                " id={:#06x}, seq={}",
+                echo.identifier, echo.sequence
+            ));
+            if let Some(reply_in) = echo.reply_in {
+                info.push_str(&format!(" (reply in {reply_in})"));
+            }
+            if let (Some(request_in), Some(round_trip)) = (echo.request_in, echo.round_trip) {
+                info.push_str(&format!(
+                    " (request in {request_in}, {})",
+                    format_round_trip(round_trip)
+                ));
+            }
+        }
+
+        if let Some(LayerData::Layer(layer)) = self.quoted.as_deref().map(Network::get_layer_data)
+        {
+            info.push_str(&format!(
+                " for {} -> {}",
+                layer.source(),
+                layer.destination()
+            ));
+        }
+
+        info
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        let [type_name, code_name, identifier_name, sequence_name] = match self.version {
+            IcmpVersion::V4 => ["icmp.type", "icmp.code", "icmp.ident", "icmp.seq"],
+            IcmpVersion::V6 => [
+                "icmpv6.type",
+                "icmpv6.code",
+                "icmpv6.echo.identifier",
+                "icmpv6.echo.sequence_number",
+            ],
+        };
+        let mut fields = vec![
+            Field::new(type_name, self.icmp_type),
+            Field::new(code_name, self.code),
+        ];
+        if let Some(echo) = &self.echo {
+            fields.push(Field::new(identifier_name, echo.identifier));
+            fields.push(Field::new(sequence_name, echo.sequence));
+        }
+        fields
+    }
+
+    fn tree(&self) -> TreeNode {
+        let type_name = self.type_name();
+        let code_name = self.code_name().unwrap_or("none");
+        let mut children = vec![
+            TreeNode::at(format!("type: {type_name} ({})", self.icmp_type), 0, 1),
+            TreeNode::at(format!("code: {code_name} ({})", self.code), 1, 1),
+            TreeNode::at(format!("checksum: {}", self.checksum), 2, 2),
+        ];
+
+        if let Some(echo) = &self.echo {
+            children.push(TreeNode::at(
+                format!("identifier: {}", echo.identifier),
+                4,
+                2,
+            ));
+            children.push(TreeNode::at(format!("sequence: {}", echo.sequence), 6, 2));
+            if let Some(request_in) = echo.request_in {
+                children.push(TreeNode::new(format!("request in: {request_in}")));
+            }
+            if let Some(reply_in) = echo.reply_in {
+                children.push(TreeNode::new(format!("reply in: {reply_in}")));
+            }
+            if let Some(round_trip) = echo.round_trip {
+                children.push(TreeNode::new(format!(
+                    "round trip: {}",
+                    format_round_trip(round_trip)
+                )));
+            }
+        }
+
+        // the quoted packet's layers, placed after the 8 byte icmp header
+        if let Some(LayerData::Layer(layer)) = self.quoted.as_deref().map(Network::get_layer_data) {
+            let mut quoted = vec![];
+            let mut offset = Some(8);
+            let mut next = LayerData::Layer(layer);
+            while let LayerData::Layer(layer) = next {
+                let mut node = layer.tree();
+                node.place(offset, usize::MAX);
+                quoted.push(node);
+                offset = offset
+                    .zip(layer.header_len())
+                    .map(|(offset, len)| offset + len);
+                next = layer.get_next();
+            }
+            let label = format!("quoted {} packet", layer.protocol());
+            children.push(TreeNode::at(label, 8, self.payload.len()).with_children(quoted));
+        }
+
+        let label = format!("{}, {type_name}", self.protocol());
+        TreeNode::at(label, 0, 8).with_children(children)
+    }
+
+    fn header_len(&self) -> Option<usize> {
+        Some(8)
+    }
+}
+
+fn format_round_trip(round_trip: Duration) -> String {
+    let micros = round_trip.num_microseconds().unwrap_or(i64::MAX);
+    format!("{:.3} ms", micros as f64 / 1000.0)
+}
@@ -1,2 +1,4 @@
+pub mod icmp;
 pub mod tcp;
+pub mod transport_parser;
 pub mod udp;
@@ -1,6 +1,7 @@
-use crate::packets::application::app_parser::parse_app_layer;
+use crate::packets::fields::Field;
 use crate::packets::packet_traits::Layer;
 use crate::packets::shared_objs::{Application, LayerData, Protocol};
+use crate::packets::tree::TreeNode;
 use pnet::packet::Packet;
 use std::borrow::Cow;
 
@@ -19,12 +20,12 @@ pub struct TcpHeader {
 
 #[derive(Debug, Clone, Default)]
 pub struct TcpFlags {
-    urg: bool,
-    ack: bool,
-    psh: bool,
-    rst: bool,
-    syn: bool,
-    fin: bool,
+    pub urg: bool,
+    pub ack: bool,
+    pub psh: bool,
+    pub rst: bool,
+    pub syn: bool,
+    pub fin: bool,
 }
 
 impl TcpHeader {
@@ -40,10 +41,40 @@ impl TcpHeader {
     }
 }
 
+// what stream reassembly made of a segment, nothing means it arrived in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpAnalysis {
+    Retransmission,
+    OutOfOrder,
+    Overlap,
+    PreviousSegmentNotCaptured,
+}
+
+impl TcpAnalysis {
+    fn description(&self) -> &str {
+        match self {
+            TcpAnalysis::Retransmission => "Retransmission",
+            TcpAnalysis::OutOfOrder => "Out-Of-Order",
+            TcpAnalysis::Overlap => "Overlapping data",
+            TcpAnalysis::PreviousSegmentNotCaptured => "Previous segment not captured",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TcpPacket {
     pub header: TcpHeader,
+    // raw segment data until stream reassembly hands it to an application dissector,
+    // then the first message the segment finished
     pub payload: Application,
+    // any further messages finished by the same segment, e.g. the TLS records
+    // after a ServerHello or pipelined HTTP requests
+    pub more_messages: Vec<Application>,
+    pub segment_length: usize,
+    pub stream: Option<u32>,
+    pub analysis: Option<TcpAnalysis>,
+    // on the segment that completed an application message, every segment it spans
+    pub pdu_frames: Box<[i32]>,
 }
 
 impl TcpPacket {
@@ -62,9 +93,23 @@ impl TcpPacket {
             flags: TcpHeader::set_tcp_flags(packet.get_flags()),
         };
 
-        let payload = parse_app_layer(packet.payload());
+        // application data can span segments, it's dissected during stream reassembly
+        let payload = Application::Other(packet.payload().to_vec().into_boxed_slice());
+
+        Some(TcpPacket {
+            header,
+            payload,
+            more_messages: Vec::new(),
+            segment_length: packet.payload().len(),
+            stream: None,
+            analysis: None,
+            pdu_frames: Box::new([]),
+        })
+    }
 
-        Some(TcpPacket { header, payload })
+    // every message in the segment, `payload` first
+    pub fn messages_mut(&mut self) -> impl Iterator<Item = &mut Application> {
+        std::iter::once(&mut self.payload).chain(self.more_messages.iter_mut())
     }
 }
 
@@ -73,7 +118,7 @@ impl Layer for TcpPacket {
         let TcpHeader {
             source_port,
             destination_port,
-            sequence_number: _,
+            sequence_number,
             acknowledgment_number,
             data_offset_reserved_flags,
             window_size,
@@ -91,28 +136,40 @@ impl Layer for TcpPacket {
         } = &self.header;
         let [urg, ack, psh, rst, syn, fin] = [*urg, *ack, *psh, *rst, *syn, *fin].map(u8::from);
 
+        let mut stream_string = String::new();
+        if let Some(stream) = self.stream {
+            stream_string.push_str(&format!("\nstream: {stream}"));
+        }
+        if let Some(analysis) = self.analysis {
+            stream_string.push_str(&format!("\nanalysis: {}", analysis.description()));
+        }
+        if !self.pdu_frames.is_empty() {
+            let frames = { self.pdu_frames.iter() }
+                .map(i32::to_string)
+                .collect::<Vec<String>>()
+                .join(", ");
+            stream_string.push_str(&format!("\nreassembled from frames: {frames}"));
+        }
+
         format!(
             "source_port: {source_port}
 destination_port: {destination_port}
+sequence_number: {sequence_number}
+segment_length: {}
 acknowledgment_number: {acknowledgment_number}
 data_offset_reserved_flags: {data_offset_reserved_flags}
 window_size: {window_size}
 checksum: {checksum}
 urgent_pointer: {urgent_pointer}
-flags: ack : {ack}, psh : {psh}, rst : {rst}, syn : {syn}, fin : {fin}, urg : {urg}"
+flags: ack : {ack}, psh : {psh}, rst : {rst}, syn : {syn}, fin : {fin}, urg : {urg}{stream_string}",
+            self.segment_length
         )
     }
     fn protocol(&self) -> Protocol {
         Protocol::TCP
     }
-    fn get_next(&self) -> LayerData {
-        match &self.payload {
-            //Application::HttpRequest(d) => {},
-            //Application::HttpResponse(d) => {},
-            Application::Dns(dns_message) => LayerData::Application(dns_message),
-            Application::Other(bytes) => LayerData::Data(bytes),
-            //Application::Tls(_) => todo!(),
-        }
+    fn get_next(&self) -> LayerData<'_> {
+        self.payload.get_layer_data()
     }
 
     fn source(&self) -> Cow<'_, str> {
@@ -124,9 +181,127 @@ flags: ack : {ack}, psh : {psh}, rst : {rst}, syn : {syn}, fin : {fin}, urg : {u
     }
 
     fn info(&self) -> String {
+        let analysis = match self.analysis {
+            Some(analysis) => format!("[{}] ", analysis.description()),
+            None => String::new(),
+        };
         format!(
-            "TCP Source Port {} -> Destination {}",
-            self.header.source_port, self.header.destination_port
+            "{analysis}TCP Source Port {} -> Destination {} Len={}",
+            self.header.source_port, self.header.destination_port, self.segment_length
         )
     }
+
+    fn fields(&self) -> Vec<Field> {
+        let header = &self.header;
+        let mut fields = vec![
+            Field::new("tcp.srcport", header.source_port),
+            Field::new("tcp.dstport", header.destination_port),
+            Field::new("tcp.port", header.source_port),
+            Field::new("tcp.port", header.destination_port),
+            Field::new("tcp.len", self.segment_length as u64),
+            Field::new("tcp.seq", header.sequence_number),
+            Field::new("tcp.ack", header.acknowledgment_number),
+            // data offset is in 32 bit words
+            Field::new("tcp.hdr_len", header.data_offset_reserved_flags as u32 * 4),
+            Field::new("tcp.window_size_value", header.window_size),
+            Field::new("tcp.flags.urg", header.flags.urg),
+            Field::new("tcp.flags.ack", header.flags.ack),
+            Field::new("tcp.flags.push", header.flags.psh),
+            Field::new("tcp.flags.reset", header.flags.rst),
+            Field::new("tcp.flags.syn", header.flags.syn),
+            Field::new("tcp.flags.fin", header.flags.fin),
+        ];
+        fields.extend(self.stream.map(|stream| Field::new("tcp.stream", stream)));
+        // like wireshark the analysis fields are only there when they're set
+        if let Some(analysis) = self.analysis {
+            let name = match analysis {
+                TcpAnalysis::Retransmission => "tcp.analysis.retransmission",
+                TcpAnalysis::OutOfOrder => "tcp.analysis.out_of_order",
+                TcpAnalysis::Overlap => "tcp.analysis.overlap",
+                TcpAnalysis::PreviousSegmentNotCaptured => "tcp.analysis.lost_segment",
+            };
+            fields.push(Field::new("tcp.analysis.flags", true));
+            fields.push(Field::new(name, true));
+        }
+        fields
+    }
+
+    fn tree(&self) -> TreeNode {
+        let header = &self.header;
+        let header_length = header.data_offset_reserved_flags as usize * 4;
+        let flags = [
+            ("urg", header.flags.urg),
+            ("ack", header.flags.ack),
+            ("psh", header.flags.psh),
+            ("rst", header.flags.rst),
+            ("syn", header.flags.syn),
+            ("fin", header.flags.fin),
+        ];
+        let set_flags = { flags.iter() }
+            .filter(|(_, set)| *set)
+            .map(|(name, _)| *name)
+            .collect::<Vec<&str>>()
+            .join(", ");
+
+        let mut children = vec![
+            TreeNode::at(format!("source_port: {}", header.source_port), 0, 2),
+            TreeNode::at(
+                format!("destination_port: {}", header.destination_port),
+                2,
+                2,
+            ),
+            TreeNode::at(format!("sequence_number: {}", header.sequence_number), 4, 4),
+            TreeNode::at(
+                format!("acknowledgment_number: {}", header.acknowledgment_number),
+                8,
+                4,
+            ),
+            TreeNode::at(format!("header_length: {header_length}"), 12, 1),
+            TreeNode::at(format!("flags: {set_flags}"), 13, 1).with_children(
+                { flags.iter() }
+                    .map(|(name, set)| TreeNode::at(format!("{name} : {}", u8::from(*set)), 13, 1))
+                    .collect(),
+            ),
+            TreeNode::at(format!("window_size: {}", header.window_size), 14, 2),
+            TreeNode::at(format!("checksum: {}", header.checksum), 16, 2),
+            TreeNode::at(format!("urgent_pointer: {}", header.urgent_pointer), 18, 2),
+        ];
+        if header_length > 20 {
+            children.push(TreeNode::at("options", 20, header_length - 20));
+        }
+        children.push(TreeNode::new(format!(
+            "segment_length: {}",
+            self.segment_length
+        )));
+        if let Some(stream) = self.stream {
+            children.push(TreeNode::new(format!("stream: {stream}")));
+        }
+        if let Some(analysis) = self.analysis {
+            children.push(TreeNode::new(format!(
+                "analysis: {}",
+                analysis.description()
+            )));
+        }
+        if !self.pdu_frames.is_empty() {
+            let frames = { self.pdu_frames.iter() }
+                .map(i32::to_string)
+                .collect::<Vec<String>>()
+                .join(", ");
+            children.push(TreeNode::new(format!("reassembled from frames: {frames}")));
+        }
+
+        let label = format!(
+            "TCP, Src Port: {}, Dst Port: {}, Len: {}",
+            header.source_port, header.destination_port, self.segment_length
+        );
+        TreeNode::at(label, 0, header_length).with_children(children)
+    }
+
+    // a message reassembled from several segments isn't in this frame's bytes
+    fn header_len(&self) -> Option<usize> {
+        match self.pdu_frames.is_empty() {
+            true => Some(self.header.data_offset_reserved_flags as usize * 4),
+            false => None,
+        }
+    }
 }
@@ -0,0 +1,21 @@
+use crate::packets::dissectors;
+use crate::packets::shared_objs::Transport;
+use crate::packets::transport::{
+    icmp::{IcmpPacket, IcmpVersion},
+    tcp::TcpPacket,
+    udp::UdpPacket,
+};
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+
+pub fn parse_transport_layer(next_header: IpNextHeaderProtocol, data: &[u8]) -> Transport {
+    match next_header {
+        IpNextHeaderProtocols::Tcp => TcpPacket::new(data).map(Transport::TCP),
+        IpNextHeaderProtocols::Udp => UdpPacket::new(data).map(Transport::UDP),
+        IpNextHeaderProtocols::Icmp => IcmpPacket::new(IcmpVersion::V4, data).map(Transport::ICMP),
+        IpNextHeaderProtocols::Icmpv6 => {
+            IcmpPacket::new(IcmpVersion::V6, data).map(Transport::ICMP)
+        }
+        _ => dissectors::dissect_ip_protocol(next_header.0, data).map(Transport::Custom),
+    }
+    .unwrap_or_else(|| Transport::Other(data.to_vec().into_boxed_slice()))
+}
@@ -1,6 +1,8 @@
 use crate::packets::application::app_parser::parse_app_layer;
+use crate::packets::fields::Field;
 use crate::packets::packet_traits::Layer;
 use crate::packets::shared_objs::{Application, LayerData, Protocol};
+use crate::packets::tree::TreeNode;
 use pnet::packet::Packet;
 use std::borrow::Cow;
 
@@ -31,7 +33,11 @@ impl UdpPacket {
             malformed: false,
         };
 
-        let payload = parse_app_layer(packet.payload());
+        let payload = parse_app_layer(
+            header.source_port,
+            header.destination_port,
+            packet.payload(),
+        );
 
         Some(UdpPacket { header, payload })
     }
@@ -59,14 +65,8 @@ malformed: {malformed}"
     fn protocol(&self) -> Protocol {
         Protocol::UDP
     }
-    fn get_next(&self) -> LayerData {
-        match &self.payload {
-            //Application::HttpRequest(_) => todo!(),
-            //Application::HttpResponse(_) => todo!(),
-            Application::Dns(dns_message) => LayerData::Application(dns_message),
-            Application::Other(bytes) => LayerData::Data(bytes),
-            //Application::Tls(_) => todo!(),
-        }
+    fn get_next(&self) -> LayerData<'_> {
+        self.payload.get_layer_data()
     }
 
     fn source(&self) -> Cow<'_, str> {
@@ -83,4 +83,41 @@ malformed: {malformed}"
             self.header.source_port, self.header.destination_port
         )
     }
+
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            Field::new("udp.srcport", self.header.source_port),
+            Field::new("udp.dstport", self.header.destination_port),
+            Field::new("udp.port", self.header.source_port),
+            Field::new("udp.port", self.header.destination_port),
+            Field::new("udp.length", self.header.length),
+        ]
+    }
+
+    fn tree(&self) -> TreeNode {
+        let UdpHeader {
+            source_port,
+            destination_port,
+            length,
+            checksum,
+            malformed,
+        } = &self.header;
+
+        let mut children = vec![
+            TreeNode::at(format!("source_port: {source_port}"), 0, 2),
+            TreeNode::at(format!("destination_port: {destination_port}"), 2, 2),
+            TreeNode::at(format!("length: {length}"), 4, 2),
+            TreeNode::at(format!("checksum: {checksum}"), 6, 2),
+        ];
+        if *malformed {
+            children.push(TreeNode::new("malformed: true"));
+        }
+
+        let label = format!("UDP, Src Port: {source_port}, Dst Port: {destination_port}");
+        TreeNode::at(label, 0, 8).with_children(children)
+    }
+
+    fn header_len(&self) -> Option<usize> {
+        Some(8)
+    }
 }
@@ -0,0 +1,116 @@
+// The drill down of a packet, what `Layer::tree` and `AppLayer::tree` build.
+// Each node can point at the bytes it was read from. A layer gives ranges
+// relative to its own first byte, `Frame::tree` then places them in the frame
+// by adding up the `Layer::header_len` of the layers in front of it.
+
+use std::iter::Peekable;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeNode {
+    pub label: String,
+    pub range: Option<Range<usize>>,
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    // a node that isn't read from any particular bytes
+    pub fn new(label: impl Into<String>) -> TreeNode {
+        TreeNode {
+            label: label.into(),
+            range: None,
+            children: vec![],
+        }
+    }
+
+    pub fn at(label: impl Into<String>, start: usize, len: usize) -> TreeNode {
+        TreeNode {
+            range: Some(start..start + len),
+            ..TreeNode::new(label)
+        }
+    }
+
+    pub fn with_children(mut self, children: Vec<TreeNode>) -> TreeNode {
+        self.children = children;
+        self
+    }
+
+    // one child per line, for layers that only have a summary. Indented lines
+    // go under the line before them.
+    pub fn from_summary(label: impl Into<String>, summary: &str) -> TreeNode {
+        let mut lines = { summary.lines() }
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| (line.len() - line.trim_start().len(), line.trim()))
+            .peekable();
+        TreeNode::new(label).with_children(nest(&mut lines, 0))
+    }
+
+    // moves every range `offset` bytes on, cutting them at `limit`. No offset
+    // means the bytes aren't in the frame, e.g. a reassembled message.
+    pub fn place(&mut self, offset: Option<usize>, limit: usize) {
+        self.range = match (self.range.take(), offset) {
+            (Some(range), Some(offset)) if range.start + offset < limit => {
+                Some(range.start + offset..(range.end + offset).min(limit))
+            }
+            _ => None,
+        };
+        for child in &mut self.children {
+            child.place(offset, limit);
+        }
+    }
+
+    // the way down to the innermost node holding the byte at `offset`, one
+    // index per level. The deepest one wins, then the last.
+    pub fn path_at(nodes: &[TreeNode], offset: usize) -> Option<Vec<usize>> {
+        { nodes.iter().enumerate() }
+            .filter_map(
+                |(i, node)| match TreeNode::path_at(&node.children, offset) {
+                    Some(mut path) => {
+                        path.insert(0, i);
+                        Some(path)
+                    }
+                    None => { node.range.as_ref() }
+                        .filter(|range| range.contains(&offset))
+                        .map(|_| vec![i]),
+                },
+            )
+            .max_by_key(Vec::len)
+    }
+
+    pub fn find<'a>(nodes: &'a [TreeNode], path: &[usize]) -> Option<&'a TreeNode> {
+        let (first, rest) = path.split_first()?;
+        let node = nodes.get(*first)?;
+        match rest.is_empty() {
+            true => Some(node),
+            false => TreeNode::find(&node.children, rest),
+        }
+    }
+}
+
+/*
+
+
+Privates
+
+
+*/
+
+// the lines indented at least `indent`, each holding the more indented ones after it
+fn nest<'a>(
+    lines: &mut Peekable<impl Iterator<Item = (usize, &'a str)>>,
+    indent: usize,
+) -> Vec<TreeNode> {
+    let mut nodes = vec![];
+    while let Some(&(line_indent, text)) = lines.peek() {
+        if line_indent < indent {
+            break;
+        }
+        lines.next();
+        let children = match lines.peek() {
+            Some(&(next_indent, _)) if next_indent > line_indent => nest(lines, next_indent),
+            _ => vec![],
+        };
+        nodes.push(TreeNode::new(text).with_children(children));
+    }
+    nodes
+}
@@ -1,43 +1,57 @@
-use crate::packets::data_link::ethernet::EthernetFrame;
+use crate::analysis::Analyzer;
+use crate::export::json::JsonWriter;
+use crate::packets::frame::Frame;
 //use iced::Error;
-use pcap::Device;
+use pcap::{Device, Linktype};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::sync::mpsc::{self, Receiver};
 use std::thread::{self, JoinHandle};
 
 #[derive(Default)]
 pub struct Sniffer {
     pub interface: Option<Device>,
-    pub receiver: Option<Receiver<EthernetFrame>>,
-    pub captured_packets: Vec<EthernetFrame>,
+    pub receiver: Option<Receiver<Frame>>,
+    pub captured_packets: Vec<Frame>,
     pub file_handle: Option<JoinHandle<()>>,
+    pub analyzer: Analyzer,
+    // bumped every time a new capture starts, so views built from
+    // `captured_packets` know to start over
+    pub generation: u32,
+    // leave out the copy of every tcp stream's payload kept for following streams
+    pub skip_stream_data: bool,
+    // every frame analysis went back and changed after it was captured, in the
+    // order it happened, so views can check them again. Repeats are possible.
+    pub changed_packets: Vec<i32>,
+    // only hold on to about this many of the latest packets, all of them when
+    // None. Analysis can't update a packet once it's gone, and
+    // `changed_packets` goes with them.
+    pub keep_last: Option<usize>,
 }
 
 impl Sniffer {
-    pub fn capture(&mut self, device: &str) {
+    // starts a live capture on a new thread. `filter` is a BPF capture filter,
+    // checked before the thread starts so a bad one comes back as an error
+    pub fn capture(&mut self, device: &str, filter: Option<&str>) -> Result<(), pcap::Error> {
+        let mut cap = pcap::Capture::from_device(device)?
+            .immediate_mode(true)
+            .promisc(true)
+            .open()?;
+        if let Some(filter) = filter {
+            cap.filter(filter, true)?;
+        }
+        let link_type = cap.get_datalink();
+
+        self.reset();
         let (sender, receiver) = mpsc::channel();
         self.receiver = Some(receiver);
-        //let interface = Some(Device::lookup().unwrap().unwrap());
-        let device = String::from(device);
         thread::spawn(move || {
             let mut index = 0;
 
-            //only for development
-            //let device = Device::lookup().unwrap().expect("Device Lookup failed");
-
-            let mut cap = pcap::Capture::from_device(&*device)
-                .unwrap()
-                .immediate_mode(true)
-                .promisc(true)
-                .open()
-                .unwrap();
-            //use when more types are captured
-            //let Linktype(_cap_type) = cap.get_datalink();
-
             while let Ok(packet) = cap.next_packet() {
-                let Some(eth_frame) = EthernetFrame::new(index, &packet) else {
-                    continue;
-                };
-                let result = sender.send(eth_frame);
+                let frame = Frame::new(index, link_type, &packet);
+                let result = sender.send(frame);
                 if result.is_err() {
                     // receiver was dropped
                     break;
@@ -45,25 +59,70 @@ impl Sniffer {
                 index += 1;
             }
         });
+        Ok(())
     }
 
-    pub fn from_file(&mut self, path: String) {
+    // reads a pcap file on a new thread, `filter` works like it does for `capture`
+    pub fn from_file(&mut self, path: String, filter: Option<&str>) -> Result<(), pcap::Error> {
+        let mut cap = pcap::Capture::from_file(path)?;
+        if let Some(filter) = filter {
+            cap.filter(filter, true)?;
+        }
+
+        let link_type = cap.get_datalink();
+
+        self.reset();
         let (sender, receiver) = mpsc::channel();
         self.receiver = Some(receiver);
 
         let handle = thread::spawn(move || {
             let mut index = 0;
-            if let Ok(mut cap) = pcap::Capture::from_file(path) {
-                while let Ok(packet) = cap.next_packet() {
-                    let Some(eth_frame) = EthernetFrame::new(index, &packet) else {
-                        continue;
-                    };
-                    let _ = sender.send(eth_frame);
-                    index += 1;
-                }
+            while let Ok(packet) = cap.next_packet() {
+                let frame = Frame::new(index, link_type, &packet);
+                let _ = sender.send(frame);
+                index += 1;
             }
         });
         self.file_handle = Some(handle);
+        Ok(())
+    }
+
+    // moves whatever the capture thread has produced into `captured_packets`
+    pub fn get_packets(&mut self) {
+        let Some(receiver) = self.receiver.as_ref() else {
+            return;
+        };
+        for frame in receiver.try_iter() {
+            self.captured_packets.push(frame);
+            let changed = self.analyzer.process(&mut self.captured_packets);
+            self.changed_packets.extend(changed);
+        }
+        self.let_go();
+    }
+
+    // waits for the next frame from the capture thread and analyzes it like
+    // `get_packets` does. None once the capture has ended. Later frames can
+    // still change the one returned.
+    pub fn next_packet(&mut self) -> Option<&Frame> {
+        let frame = self.receiver.as_ref()?.recv().ok()?;
+        self.captured_packets.push(frame);
+        let changed = self.analyzer.process(&mut self.captured_packets);
+        self.changed_packets.extend(changed);
+        self.let_go();
+        self.captured_packets.last()
+    }
+
+    // parses every frame again from its bytes, for when the decode as rules changed
+    pub fn redissect(&mut self) {
+        let packets = std::mem::take(&mut self.captured_packets);
+        self.analyzer = self.new_analyzer();
+        for frame in packets {
+            let packet = pcap::Packet::new(&frame.packet_header, &frame.data);
+            self.captured_packets
+                .push(Frame::new(frame.id, frame.link_type, &packet));
+            let changed = self.analyzer.process(&mut self.captured_packets);
+            self.changed_packets.extend(changed);
+        }
     }
 
     pub fn stop(&mut self) {
@@ -71,235 +130,142 @@ impl Sniffer {
         self.receiver = None;
     }
 
+    fn reset(&mut self) {
+        self.captured_packets = vec![];
+        self.changed_packets = vec![];
+        self.analyzer = self.new_analyzer();
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    // drops the oldest packets past `keep_last`, in batches so it isn't every frame
+    fn let_go(&mut self) {
+        let Some(keep) = self.keep_last else {
+            return;
+        };
+        if self.captured_packets.len() > keep.saturating_mul(2) {
+            let extra = self.captured_packets.len() - keep;
+            self.captured_packets.drain(..extra);
+            self.changed_packets.clear();
+        }
+    }
+
+    fn new_analyzer(&self) -> Analyzer {
+        match self.skip_stream_data {
+            true => Analyzer::without_stream_data(),
+            false => Analyzer::default(),
+        }
+    }
+
     pub fn get_interfaces(&mut self) -> Result<Vec<Device>, pcap::Error> {
         let devices = pcap::Device::list().expect("no devices");
         Ok(devices)
     }
+
+    // pcapng when the extension says so, classic pcap otherwise
+    pub fn save(&self, path: &Path) -> Result<(), pcap::Error> {
+        let is_pcapng = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pcapng"));
+        // a capture only ever has the one link type
+        let link_type = self
+            .captured_packets
+            .first()
+            .map_or(Linktype::ETHERNET, |p| p.link_type);
+        if is_pcapng {
+            write_pcapng(path, link_type, &self.captured_packets)
+        } else {
+            write_pcap(path, link_type, &self.captured_packets)
+        }
+    }
+
+    // a JSON array, or one frame per line for `.ndjson` and `.jsonl`
+    pub fn export_json(&self, path: &Path) -> std::io::Result<()> {
+        let ndjson = { path.extension() }.is_some_and(|ext| {
+            ext.eq_ignore_ascii_case("ndjson") || ext.eq_ignore_ascii_case("jsonl")
+        });
+        let mut writer = JsonWriter::new(BufWriter::new(File::create(path)?), ndjson);
+        for packet in &self.captured_packets {
+            writer.write(packet)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
 }
 
 /*
 
-the different types from datatype to ensure it only parses legit ethernet etc..
-
-
-   pub const NULL: Self = Self(0);
-   pub const ETHERNET: Self = Self(1);
-   pub const AX25: Self = Self(3);
-   pub const IEEE802_5: Self = Self(6);
-   pub const ARCNET_BSD: Self = Self(7);
-   pub const SLIP: Self = Self(8);
-   pub const PPP: Self = Self(9);
-   pub const FDDI: Self = Self(10);
-   pub const PPP_HDLC: Self = Self(50);
-   pub const PPP_ETHER: Self = Self(51);
-   pub const ATM_RFC1483: Self = Self(100);
-   pub const RAW: Self = Self(101);
-   pub const C_HDLC: Self = Self(104);
-   pub const IEEE802_11: Self = Self(105);
-   pub const FRELAY: Self = Self(107);
-   pub const LOOP: Self = Self(108);
-   pub const LINUX_SLL: Self = Self(113);
-   pub const LTALK: Self = Self(114);
-   pub const PFLOG: Self = Self(117);
-   pub const IEEE802_11_PRISM: Self = Self(119);
-   pub const IP_OVER_FC: Self = Self(122);
-   pub const SUNATM: Self = Self(123);
-   pub const IEEE802_11_RADIOTAP: Self = Self(127);
-   pub const ARCNET_LINUX: Self = Self(129);
-   pub const APPLE_IP_OVER_IEEE1394: Self = Self(138);
-   pub const MTP2_WITH_PHDR: Self = Self(139);
-   pub const MTP2: Self = Self(140);
-   pub const MTP3: Self = Self(141);
-   pub const SCCP: Self = Self(142);
-   pub const DOCSIS: Self = Self(143);
-   pub const LINUX_IRDA: Self = Self(144);
-   pub const USER0: Self = Self(147);
-   pub const USER1: Self = Self(148);
-   pub const USER2: Self = Self(149);
-   pub const USER3: Self = Self(150);
-   pub const USER4: Self = Self(151);
-   pub const USER5: Self = Self(152);
-   pub const USER6: Self = Self(153);
-   pub const USER7: Self = Self(154);
-   pub const USER8: Self = Self(155);
-   pub const USER9: Self = Self(156);
-   pub const USER10: Self = Self(157);
-   pub const USER11: Self = Self(158);
-   pub const USER12: Self = Self(159);
-   pub const USER13: Self = Self(160);
-   pub const USER14: Self = Self(161);
-   pub const USER15: Self = Self(162);
-   pub const IEEE802_11_AVS: Self = Self(163);
-   pub const BACNET_MS_TP: Self = Self(165);
-   pub const PPP_PPPD: Self = Self(166);
-   pub const GPRS_LLC: Self = Self(169);
-   pub const GPF_T: Self = Self(170);
-   pub const GPF_F: Self = Self(171);
-   pub const LINUX_LAPD: Self = Self(177);
-   pub const MFR: Self = Self(182);
-   pub const BLUETOOTH_HCI_H4: Self = Self(187);
-   pub const USB_LINUX: Self = Self(189);
-   pub const PPI: Self = Self(192);
-   pub const IEEE802_15_4_WITHFCS: Self = Self(195);
-   pub const SITA: Self = Self(196);
-   pub const ERF: Self = Self(197);
-   pub const BLUETOOTH_HCI_H4_WITH_PHDR: Self = Self(201);
-   pub const AX25_KISS: Self = Self(202);
-   pub const LAPD: Self = Self(203);
-   pub const PPP_WITH_DIR: Self = Self(204);
-   pub const C_HDLC_WITH_DIR: Self = Self(205);
-   pub const FRELAY_WITH_DIR: Self = Self(206);
-   pub const LAPB_WITH_DIR: Self = Self(207);
-   pub const IPMB_LINUX: Self = Self(209);
-   pub const IEEE802_15_4_NONASK_PHY: Self = Self(215);
-   pub const USB_LINUX_MMAPPED: Self = Self(220);
-   pub const FC_2: Self = Self(224);
-   pub const FC_2_WITH_FRAME_DELIMS: Self = Self(225);
-   pub const IPNET: Self = Self(226);
-   pub const CAN_SOCKETCAN: Self = Self(227);
-   pub const IPV4: Self = Self(228);
-   pub const IPV6: Self = Self(229);
-   pub const IEEE802_15_4_NOFCS: Self = Self(230);
-   pub const DBUS: Self = Self(231);
-   pub const DVB_CI: Self = Self(235);
-   pub const MUX27010: Self = Self(236);
-   pub const STANAG_5066_D_PDU: Self = Self(237);
-   pub const NFLOG: Self = Self(239);
-   pub const NETANALYZER: Self = Self(240);
-   pub const NETANALYZER_TRANSPARENT: Self = Self(241);
-   pub const IPOIB: Self = Self(242);
-   pub const MPEG_2_TS: Self = Self(243);
-   pub const NG40: Self = Self(244);
-   pub const NFC_LLCP: Self = Self(245);
-   pub const INFINIBAND: Self = Self(247);
-   pub const SCTP: Self = Self(248);
-   pub const USBPCAP: Self = Self(249);
-   pub const RTAC_SERIAL: Self = Self(250);
-   pub const BLUETOOTH_LE_LL: Self = Self(251);
-   pub const NETLINK: Self = Self(253);
-   pub const BLUETOOTH_LINUX_MONITOR: Self = Self(254);
-   pub const BLUETOOTH_BREDR_BB: Self = Self(255);
-   pub const BLUETOOTH_LE_LL_WITH_PHDR: Self = Self(256);
-   pub const PROFIBUS_DL: Self = Self(257);
-   pub const PKTAP: Self = Self(258);
-   pub const EPON: Self = Self(259);
-   pub const IPMI_HPM_2: Self = Self(260);
-   pub const ZWAVE_R1_R2: Self = Self(261);
-   pub const ZWAVE_R3: Self = Self(262);
-   pub const WATTSTOPPER_DLM: Self = Self(263);
-   pub const ISO_14443: Self = Self(264);
-   pub const RDS: Self = Self(265);
-   pub const USB_DARWIN: Self = Self(266);
-   pub const SDLC: Self = Self(268);
-   pub const LORATAP: Self = Self(270);
-   pub const VSOCK: Self = Self(271);
-   pub const NORDIC_BLE: Self = Self(272);
-   pub const DOCSIS31_XRA31: Self = Self(273);
-   pub const ETHERNET_MPACKET: Self = Self(274);
-   pub const DISPLAYPORT_AUX: Self = Self(275);
-   pub const LINUX_SLL2: Self = Self(276);
-   pub const OPENVIZSLA: Self = Self(278);
-   pub const EBHSCR: Self = Self(279);
-   pub const VPP_DISPATCH: Self = Self(280);
-   pub const DSA_TAG_BRCM: Self = Self(281);
-   pub const DSA_TAG_BRCM_PREPEND: Self = Self(282);
-   pub const IEEE802_15_4_TAP: Self = Self(283);
-   pub const DSA_TAG_DSA: Self = Self(284);
-   pub const DSA_TAG_EDSA: Self = Self(285);
-   pub const ELEE: Self = Self(286);
-   pub const Z_WAVE_SERIAL: Self = Self(287);
-   pub const USB_2_0: Self = Self(288);
-   pub const ATSC_ALP: Self = Self(289);
 
-*/
+Privates
 
-/*
-use std::collections::HashMap;
-use std::net::IpAddr;
-
-#[derive(Hash, Eq, PartialEq, Debug)]
-struct ConnectionKey {
-    src_ip: IpAddr,
-    dest_ip: IpAddr,
-    src_port: u16,
-    dest_port: u16,
-    protocol: String,
-}
 
-struct Connection {
-    packets: Vec<Packet>,  // Assuming you have a Packet struct defined elsewhere
-    // Other connection-specific data
-}
+*/
 
-fn main() {
-    let mut connections: HashMap<ConnectionKey, Connection> = HashMap::new();
-
-    // Example: Adding a new connection
-    let key = ConnectionKey {
-        src_ip: "192.168.1.1".parse().unwrap(),
-        dest_ip: "93.184.216.34".parse().unwrap(),
-        src_port: 12345,
-        dest_port: 80,
-        protocol: "TCP".to_string(),
-    };
-    let connection = Connection {
-        packets: vec![],  // Initialize with an empty packet list
-        // Initialize other fields as necessary
-    };
-    connections.insert(key, connection);
-
-    // Example: Accessing a connection
-    if let Some(connection) = connections.get_mut(&key) {
-        // Do something with the connection
-        println!("Found a connection with {} packets", connection.packets.len());
+fn write_pcap(path: &Path, linktype: Linktype, packets: &[Frame]) -> Result<(), pcap::Error> {
+    let mut savefile = pcap::Capture::dead(linktype)?.savefile(path)?;
+    for packet in packets {
+        savefile.write(&pcap::Packet::new(&packet.packet_header, &packet.data));
     }
+    savefile.flush()
 }
 
+// libpcap can only write classic pcap, so pcapng blocks are written by hand.
+// One section header, one interface description, then an enhanced packet block
+// per packet, all little endian with the default microsecond resolution.
+fn write_pcapng(
+    path: &Path,
+    linktype: Linktype,
+    packets: &[Frame],
+) -> Result<(), pcap::Error> {
+    let mut file = BufWriter::new(File::create(path)?);
 
-//comparing connection
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-use std::cmp::Ordering;
+    // section header block
+    write_block(&mut file, 0x0A0D_0D0A, |body| {
+        body.extend_from_slice(&0x1A2B_3C4Du32.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        // section length not specified
+        body.extend_from_slice(&(-1i64).to_le_bytes());
+    })?;
 
-#[derive(Debug, Clone)]
-struct Connection {
-    src: String,
-    dest: String,
-}
-
-impl PartialEq for Connection {
-    fn eq(&self, other: &Self) -> bool {
-        (self.src == other.src && self.dest == other.dest) || (self.src == other.dest && self.dest == other.src)
-    }
-}
-
-impl Eq for Connection {}
+    // interface description block
+    write_block(&mut file, 0x0000_0001, |body| {
+        body.extend_from_slice(&(linktype.0 as u16).to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+    })?;
 
-impl Hash for Connection {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        let mut hasher = DefaultHasher::new();
-        let mut v = vec![&self.src, &self.dest];
-        v.sort(); // Ensure consistent ordering for hashing
-        v.hash(&mut hasher);
-        state.write_u64(hasher.finish());
-    }
-}
+    for packet in packets {
+        let header = &packet.packet_header;
+        let micros = (header.ts.tv_sec as u64) * 1_000_000 + header.ts.tv_usec as u64;
 
-impl PartialOrd for Connection {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+        // enhanced packet block
+        write_block(&mut file, 0x0000_0006, |body| {
+            body.extend_from_slice(&0u32.to_le_bytes());
+            body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+            body.extend_from_slice(&(micros as u32).to_le_bytes());
+            body.extend_from_slice(&(packet.data.len() as u32).to_le_bytes());
+            body.extend_from_slice(&header.len.to_le_bytes());
+            body.extend_from_slice(&packet.data);
+        })?;
     }
-}
 
-impl Ord for Connection {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let mut v1 = vec![&self.src, &self.dest];
-        let mut v2 = vec![&other.src, &other.dest];
-        v1.sort();
-        v2.sort();
-        v1.cmp(&v2)
-    }
+    file.flush()?;
+    Ok(())
 }
 
+fn write_block(
+    writer: &mut impl Write,
+    block_type: u32,
+    fill_body: impl FnOnce(&mut Vec<u8>),
+) -> std::io::Result<()> {
+    let mut body = Vec::new();
+    fill_body(&mut body);
+    // block bodies are padded to 32 bits
+    body.resize(body.len().next_multiple_of(4), 0);
 
- */
+    let total_length = (body.len() + 12) as u32;
+    writer.write_all(&block_type.to_le_bytes())?;
+    writer.write_all(&total_length.to_le_bytes())?;
+    writer.write_all(&body)?;
+    writer.write_all(&total_length.to_le_bytes())
+}